@@ -4,19 +4,20 @@
  */
 #![no_std]
 
+use core::fmt::Write;
 use deku::{DekuContainerWrite, DekuError};
 use flagset::FlagSet;
 use hmac::Mac;
 use log::debug;
 use mctp::AsyncRespChannel;
 use nvme::{
-    AdminGetLogPageLidRequestType, LidSupportedAndEffectsFlags, LogPageAttributes,
-    mi::ResponseStatus,
+    AdminGetLogPageLidRequestType, CommandSupportedAndEffectsFlags, LidSupportedAndEffectsFlags,
+    LogPageAttributes, mi::ResponseStatus,
 };
 use uuid::Uuid;
 
 pub mod nvme;
-mod pcie;
+pub mod pcie;
 mod wire;
 
 extern crate deku;
@@ -24,7 +25,22 @@ extern crate deku;
 const MAX_CONTROLLERS: usize = 2;
 const MAX_NAMESPACES: usize = 4;
 const MAX_PORTS: usize = 2;
-const MAX_NIDTS: usize = 2;
+// Base v2.1, 5.1.13.2.3, Figure 315: One descriptor for each NIDT
+const MAX_NIDTS: usize = 4;
+// Base v2.1, 5.1.12.1.5: Fewer than the 1024 entries of the log, beyond which
+// only the overflow sentinel is reported
+const MAX_CHANGED_NAMESPACES: usize = 16;
+const MAX_SECONDARY_CONTROLLERS: usize = 4;
+const MAX_FIRMWARE_SLOTS: usize = 7;
+const MAX_DEVICE_SELF_TEST_RESULTS: usize = 20;
+// Base v2.1, 4.7
+const MAX_NQN_LEN: usize = 223;
+// LBA data sizes from 512 bytes to 64KiB
+const MIN_BLOCK_ORDER: u8 = 9;
+const MAX_BLOCK_ORDER: u8 = 16;
+// Composite Temperature thresholds just beyond the operating range
+const DEFAULT_UNDER_TEMPERATURE_THRESHOLD: u16 = 212;
+const DEFAULT_OVER_TEMPERATURE_THRESHOLD: u16 = 401;
 
 #[derive(Debug)]
 pub enum CommandEffect {
@@ -36,6 +52,11 @@ pub enum CommandEffect {
         port_id: PortId,
         freq: nvme::mi::SmbusFrequency,
     },
+    VirtualizationManagement {
+        cntlid: ControllerId,
+        act: nvme::VirtualizationManagementAction,
+        resources: Option<(nvme::ControllerResourceType, u16)>,
+    },
 }
 
 #[derive(Debug)]
@@ -44,14 +65,25 @@ pub enum CommandEffectError {
     InternalError,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum HandleError {
+    IntegrityCheckFailed,
+    IntegrityCheckRequired,
+    NotRequest,
+    PortInactive,
+    PortUnrecognised,
+    Truncated,
+    UnsupportedMessageType,
+}
+
 trait RequestHandler {
     type Ctx;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         app: A,
@@ -61,11 +93,49 @@ trait RequestHandler {
         C: AsyncRespChannel;
 }
 
+// Large responses are encoded into the management endpoint's scratch buffer
+// using encode_into() to avoid placing multiple copies on the stack
+const MAX_ENCODE_SIZE: usize = MAX_ADMIN_DATA_LEN as usize;
+
+// MI v2.0, 6, Figure 136: DLEN bounds Admin Command response data to 4KiB
+const MAX_ADMIN_DATA_LEN: u32 = 4096;
+
+// Base v2.1, 3.1.4.1, CAP.MPSMIN is zero
+const MIN_MEMORY_PAGE_SIZE: u64 = 4096;
+
+// MI v2.0, 5.2.3: The MCTP baseline transmission unit size bounds MTUS below
+const MCTP_BASELINE_MTU: u16 = 64;
+
 trait Encode<const S: usize>: DekuContainerWrite {
     fn encode(&self) -> Result<([u8; S], usize), DekuError> {
         let mut buf = [0u8; S];
         self.to_slice(&mut buf).map(|len| (buf, len))
     }
+
+    fn encode_into<'b>(&self, buf: &'b mut [u8]) -> Result<(&'b [u8], usize), DekuError> {
+        if buf.len() < S {
+            return Err(deku::deku_error!(
+                DekuError::InvalidParam,
+                "Scratch buffer is smaller than encoded size",
+                "{} is less than {}",
+                buf.len(),
+                S
+            ));
+        }
+        let buf = &mut buf[..S];
+        buf.fill(0);
+        self.to_slice(buf).map(|len| (&*buf, len))
+    }
+
+    // Encodes only the bytes of the response within [offset, offset + buf.len())
+    fn encode_window<'b>(&self, offset: usize, buf: &'b mut [u8]) -> Result<&'b [u8], DekuError> {
+        buf.fill(0);
+        let mut window = wire::WindowWriter::new(offset, buf);
+        let mut writer = deku::writer::Writer::new(&mut window);
+        self.to_writer(&mut writer, ())?;
+        writer.finalize()?;
+        Ok(buf)
+    }
 }
 
 /// # Safety
@@ -80,11 +150,14 @@ unsafe trait Discriminant<T: Copy> {
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct PciePort {
+    // The function number is a property of each controller on the port
     b: u16,
     d: u16,
-    f: u16,
     seg: u8,
+    // MI v2.0, 5.7.2, Figure 115
     mps: nvme::mi::PciePayloadSize,
+    // Highest supported link speed, from which PCIESLSV is derived
+    mls: nvme::mi::PcieLinkSpeed,
     cls: nvme::mi::PcieLinkSpeed,
     mlw: nvme::mi::PcieLinkWidth,
     nlw: nvme::mi::PcieLinkWidth,
@@ -95,14 +168,48 @@ impl PciePort {
         Self {
             b: 0,
             d: 0,
-            f: 0,
             seg: 0,
             mps: nvme::mi::PciePayloadSize::Payload128B,
+            mls: nvme::mi::PcieLinkSpeed::Gts64,
             cls: nvme::mi::PcieLinkSpeed::Gts2p5,
             mlw: nvme::mi::PcieLinkWidth::X2,
             nlw: nvme::mi::PcieLinkWidth::X1,
         }
     }
+
+    pub fn builder() -> PciePortBuilder {
+        Default::default()
+    }
+
+    fn link_active(&self) -> bool {
+        self.cls != nvme::mi::PcieLinkSpeed::Inactive
+    }
+
+    // MI v2.0, 5.7.2, Figure 115, PCIESLSV
+    fn supported_link_speeds(&self) -> u8 {
+        // Bit 0 represents 2.5GT/s, and each supported speed implies support
+        // for all slower speeds
+        ((1u16 << u8::from(self.mls)) - 1) as u8
+    }
+
+    // MI v2.0, 5.7.2, Figure 115, PCIECLS
+    fn current_link_speed(&self) -> u8 {
+        if self.link_active() {
+            self.cls.into()
+        } else {
+            0
+        }
+    }
+
+    // MI v2.0, 5.7.2, Figure 115, PCIENLW
+    fn negotiated_link_width(&self) -> u8 {
+        // No width is negotiated while the link is down
+        if self.link_active() {
+            self.nlw.into()
+        } else {
+            0
+        }
+    }
 }
 
 impl Default for PciePort {
@@ -111,6 +218,69 @@ impl Default for PciePort {
     }
 }
 
+pub struct PciePortBuilder {
+    mps: nvme::mi::PciePayloadSize,
+    mls: nvme::mi::PcieLinkSpeed,
+    cls: nvme::mi::PcieLinkSpeed,
+    mlw: nvme::mi::PcieLinkWidth,
+    nlw: nvme::mi::PcieLinkWidth,
+}
+
+impl PciePortBuilder {
+    pub fn new() -> Self {
+        let pprt = PciePort::new();
+        Self {
+            mps: pprt.mps,
+            mls: pprt.mls,
+            cls: pprt.cls,
+            mlw: pprt.mlw,
+            nlw: pprt.nlw,
+        }
+    }
+
+    pub fn mps(&mut self, mps: nvme::mi::PciePayloadSize) -> &mut Self {
+        self.mps = mps;
+        self
+    }
+
+    pub fn mls(&mut self, speed: nvme::mi::PcieLinkSpeed) -> &mut Self {
+        self.mls = speed;
+        self
+    }
+
+    pub fn cls(&mut self, speed: nvme::mi::PcieLinkSpeed) -> &mut Self {
+        self.cls = speed;
+        self
+    }
+
+    pub fn mlw(&mut self, width: nvme::mi::PcieLinkWidth) -> &mut Self {
+        self.mlw = width;
+        self
+    }
+
+    pub fn nlw(&mut self, width: nvme::mi::PcieLinkWidth) -> &mut Self {
+        self.nlw = width;
+        self
+    }
+
+    pub fn build(&self) -> PciePort {
+        PciePort {
+            mps: self.mps,
+            mls: self.mls,
+            cls: self.cls,
+            mlw: self.mlw,
+            nlw: self.nlw,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PciePortBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct TwoWirePort {
     // MI v2.0, 5.7.2, Figure 116
@@ -149,24 +319,64 @@ impl Default for TwoWirePort {
 }
 
 pub struct TwoWirePortBuilder {
+    cvpdaddr: u8,
+    mvpdfreq: nvme::mi::SmbusFrequency,
+    cmeaddr: u8,
+    i3csprt: bool,
     msmbfreq: nvme::mi::SmbusFrequency,
+    nvmebms: bool,
 }
 
 impl TwoWirePortBuilder {
     pub fn new() -> Self {
         Self {
+            cvpdaddr: 0,
+            mvpdfreq: nvme::mi::SmbusFrequency::FreqNotSupported,
+            cmeaddr: 0x1d,
+            i3csprt: false,
             msmbfreq: nvme::mi::SmbusFrequency::Freq100Khz,
+            nvmebms: false,
         }
     }
 
+    pub fn cvpdaddr(&mut self, addr: u8) -> &mut Self {
+        self.cvpdaddr = addr;
+        self
+    }
+
+    pub fn mvpdfreq(&mut self, freq: nvme::mi::SmbusFrequency) -> &mut Self {
+        self.mvpdfreq = freq;
+        self
+    }
+
+    pub fn cmeaddr(&mut self, addr: u8) -> &mut Self {
+        self.cmeaddr = addr;
+        self
+    }
+
+    pub fn i3csprt(&mut self, supported: bool) -> &mut Self {
+        self.i3csprt = supported;
+        self
+    }
+
     pub fn msmbfreq(&mut self, freq: nvme::mi::SmbusFrequency) -> &mut Self {
         self.msmbfreq = freq;
         self
     }
 
+    pub fn nvmebms(&mut self, supported: bool) -> &mut Self {
+        self.nvmebms = supported;
+        self
+    }
+
     pub fn build(&self) -> TwoWirePort {
         TwoWirePort {
+            cvpdaddr: self.cvpdaddr,
+            mvpdfreq: self.mvpdfreq,
+            cmeaddr: self.cmeaddr,
+            i3csprt: self.i3csprt,
             msmbfreq: self.msmbfreq,
+            nvmebms: self.nvmebms,
             ..Default::default()
         }
     }
@@ -212,6 +422,7 @@ pub struct Port {
     mebs: u32,
     // Local state
     mtus: u16,
+    lmtus: Option<u16>,
 }
 
 impl Port {
@@ -220,51 +431,145 @@ impl Port {
             id,
             typ,
             caps: PortCapabilities::new(),
-            mmtus: 64,
+            mmtus: MCTP_BASELINE_MTU,
             mebs: 0,
-            mtus: 64,
+            mtus: MCTP_BASELINE_MTU,
+            lmtus: None,
         }
     }
+
+    pub fn id(&self) -> PortId {
+        self.id
+    }
+
+    pub fn typ(&self) -> &PortType {
+        &self.typ
+    }
+
+    pub fn set_link_mtu(&mut self, mtus: u16) {
+        self.lmtus = Some(mtus);
+    }
+
+    fn effective_mtus(&self) -> u16 {
+        self.lmtus
+            .map_or(self.mtus, |lmtus| core::cmp::min(self.mtus, lmtus))
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PortId(u8);
 
+impl PortId {
+    pub const fn new(id: u8) -> Self {
+        Self(id)
+    }
+
+    pub const fn value(&self) -> u8 {
+        self.0
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 struct ManagementEndpointControllerState {
     cc: nvme::ControllerConfiguration,
     csts: FlagSet<nvme::ControllerStatusFlags>,
     chscf: FlagSet<nvme::mi::ControllerHealthStatusChangedFlags>,
+    csts_latched: FlagSet<nvme::mi::ControllerStatusFlags>,
 }
 
+// MI v2.0, 3.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageIntegrityCheck {
+    // Digest state once the seed has absorbed the prefix
+    initial: u32,
+}
+
+pub type ManagementEndpoint = GenericManagementEndpoint<MAX_CONTROLLERS>;
+
 #[derive(Debug)]
-pub struct ManagementEndpoint {
-    #[expect(dead_code)]
+pub struct GenericManagementEndpoint<const CTLRS: usize> {
     port: PortId,
-    mecss: [ManagementEndpointControllerState; MAX_CONTROLLERS],
+    mecss: [ManagementEndpointControllerState; CTLRS],
     ccsf: nvme::mi::CompositeControllerStatusFlagSet,
+    // MI v2.0, 5.1.4: Supported and enabled asynchronous events, indexed by AEI
+    aes: u64,
+    aee: u64,
+    // Local state: command slot of the request being serviced
+    csi: bool,
+    mic: MessageIntegrityCheck,
+    // Local state: encoding space for large response bodies
+    scratch: [u8; MAX_ENCODE_SIZE],
 }
 
-impl ManagementEndpoint {
+impl<const CTLRS: usize> GenericManagementEndpoint<CTLRS> {
     pub fn new(port: PortId) -> Self {
         Self {
             port,
-            mecss: [ManagementEndpointControllerState::default(); MAX_CONTROLLERS],
+            mecss: [ManagementEndpointControllerState::default(); CTLRS],
             ccsf: nvme::mi::CompositeControllerStatusFlagSet::empty(),
+            aes: 0,
+            aee: 0,
+            csi: false,
+            mic: MessageIntegrityCheck::default(),
+            scratch: [0; MAX_ENCODE_SIZE],
+        }
+    }
+
+    pub fn set_message_integrity_check(&mut self, mic: MessageIntegrityCheck) {
+        self.mic = mic;
+    }
+
+    pub fn set_supported_async_events(&mut self, aes: u64) {
+        self.aes = aes;
+        self.aee &= aes;
+    }
+
+    pub fn async_events_enabled(&self) -> u64 {
+        self.aee
+    }
+
+    // Logs the fragments of a response as offset-annotated hex, sixteen bytes per line
+    #[cfg(feature = "trace-responses")]
+    pub fn trace_response(bufs: &[&[u8]]) {
+        let mut line = [0u8; 16];
+        let mut len = 0;
+        let mut offset = 0;
+
+        for b in bufs.iter().flat_map(|buf| buf.iter()) {
+            line[len] = *b;
+            len += 1;
+            if len == line.len() {
+                log::trace!("{offset:04x}: {:02x?}", &line[..len]);
+                offset += len;
+                len = 0;
+            }
+        }
+
+        if len != 0 {
+            log::trace!("{offset:04x}: {:02x?}", &line[..len]);
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 struct MiCapability {
     mjr: u8,
     mnr: u8,
 }
 
 impl MiCapability {
+    // NVMe-MI revisions that may be reported
+    const VERSIONS: [(u8, u8); 4] = [(1, 0), (1, 1), (1, 2), (2, 0)];
+
     fn new() -> Self {
         Self { mjr: 1, mnr: 2 }
     }
+
+    fn with_version(mjr: u8, mnr: u8) -> Option<Self> {
+        Self::VERSIONS
+            .contains(&(mjr, mnr))
+            .then_some(Self { mjr, mnr })
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -292,87 +597,360 @@ impl<T> OperatingRange<T> {
     }
 }
 
+impl OperatingRange<u16> {
+    fn temperature(&self, temp: Temperature<u16>) -> Option<u16> {
+        match (&self.kind, temp) {
+            (UnitKind::Kelvin, Temperature::Kelvin(k)) => Some(k),
+            (UnitKind::Kelvin, Temperature::Celcius(c)) => c.checked_add(273),
+            (UnitKind::Percent, _) => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct ControllerId(u16);
 
+impl ControllerId {
+    pub const fn new(id: u16) -> Self {
+        Self(id)
+    }
+
+    pub const fn value(&self) -> u16 {
+        self.0
+    }
+}
+
 #[derive(Debug)]
 pub struct SecondaryController {
-    #[expect(dead_code)]
     id: ControllerId,
+    vfn: u16,
+    nvq: u16,
+    nvi: u16,
+    online: bool,
+}
+
+// Flexible resources managed by a primary controller for its secondaries
+#[derive(Debug)]
+struct FlexibleResources {
+    // Total flexible resources
+    frt: u32,
+    // Flexible resources allocated to the primary controller
+    rfap: u16,
+    // Private resources of the primary controller
+    prt: u16,
+    // Maximum flexible resources assignable to a secondary controller
+    frsm: u16,
+    // Preferred granularity of assignment
+    gran: u16,
+}
+
+#[derive(Debug)]
+struct ControllerVirtualization {
+    vq: FlexibleResources,
+    vi: FlexibleResources,
+}
+
+impl ControllerVirtualization {
+    fn new() -> Self {
+        Self {
+            vq: FlexibleResources {
+                frt: 64,
+                rfap: 0,
+                prt: 2,
+                frsm: 8,
+                gran: 1,
+            },
+            vi: FlexibleResources {
+                frt: 64,
+                rfap: 0,
+                prt: 2,
+                frsm: 8,
+                gran: 1,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum ControllerType {
+pub enum ControllerType {
     Io,
-    #[expect(dead_code)]
     Discovery,
-    #[expect(dead_code)]
     Administrative,
 }
 
+// Base v2.1, 5.1.12.1.5
+#[derive(Debug)]
+struct ChangedNamespaceList {
+    nsids: heapless::Vec<NamespaceId, MAX_CHANGED_NAMESPACES>,
+    overflow: bool,
+}
+
+impl ChangedNamespaceList {
+    fn new() -> Self {
+        Self {
+            nsids: heapless::Vec::new(),
+            overflow: false,
+        }
+    }
+
+    fn record(&mut self, nsid: NamespaceId) {
+        if self.overflow {
+            return;
+        }
+
+        // Entries are reported in increasing NSID order
+        let Err(idx) = self.nsids.binary_search_by_key(&nsid.0, |ns| ns.0) else {
+            return;
+        };
+
+        if self.nsids.insert(idx, nsid).is_err() {
+            debug!(
+                "Changed namespace list overflowed recording NSID {}",
+                nsid.0
+            );
+            self.nsids.clear();
+            self.overflow = true;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.nsids.clear();
+        self.overflow = false;
+    }
+}
+
+pub type Controller = GenericController<MAX_NAMESPACES>;
+
 #[derive(Debug)]
-pub struct Controller {
+pub struct GenericController<const NSS: usize> {
     id: ControllerId,
     cntrltype: ControllerType,
     port: PortId,
-    secondaries: heapless::Vec<SecondaryController, 0>,
-    active_ns: heapless::Vec<NamespaceId, MAX_NAMESPACES>,
+    func: u8,
+    secondaries: heapless::Vec<SecondaryController, MAX_SECONDARY_CONTROLLERS>,
+    active_ns: heapless::Vec<NamespaceId, NSS>,
     temp: u16,
     temp_range: OperatingRange<u16>,
+    wctemp: u16,
+    cctemp: u16,
+    // Base v2.1, 5.1.25, Temperature Threshold
+    tmpth: OperatingRange<u16>,
     capacity: u64,
+    // Available spare in the same units as capacity
     spare: u64,
-    spare_range: OperatingRange<u64>,
+    spare_range: OperatingRange<u8>,
     write_age: u64,
     write_lifespan: u64,
+    rd: bool,
     ro: bool,
+    vmbf: bool,
+    pmrro: bool,
     cc: nvme::ControllerConfiguration,
     csts: FlagSet<nvme::ControllerStatusFlags>,
     lpa: FlagSet<LogPageAttributes>,
+    oacs: FlagSet<nvme::OptionalAdminCommandSupport>,
+    // 0's based
+    acl: u8,
+    // 0's based
+    aerl: u8,
+    // Base v2.1, 5.1.25, Number of Queues: 0's based
+    nsqa: u16,
+    ncqa: u16,
     lsaes: [FlagSet<LidSupportedAndEffectsFlags>; 130],
+    // Base v2.1, 5.1.12.1, Commands Supported and Effects: Indexed by admin opcode
+    acses: [FlagSet<CommandSupportedAndEffectsFlags>; 256],
     fna: FlagSet<nvme::FormatNvmAttributes>,
+    chns: ChangedNamespaceList,
+    iocs_supported: FlagSet<nvme::IoCommandSetFlags>,
+    iocs_enabled: FlagSet<nvme::IoCommandSetFlags>,
+    cwarn_latch: FlagSet<nvme::mi::CriticalWarningFlags>,
+    cwarn_latched: FlagSet<nvme::mi::CriticalWarningFlags>,
+    // Base v2.1, 5.1.25, Asynchronous Event Configuration
+    aec: FlagSet<nvme::mi::CriticalWarningFlags>,
+    // Local state: Critical warning conditions at the last update, and the events
+    // they have generated
+    cwarn_asserted: FlagSet<nvme::mi::CriticalWarningFlags>,
+    aens: FlagSet<nvme::mi::CriticalWarningFlags>,
+    // Local state: Asynchronous Event Requests that completed without an event, which
+    // a controller would hold outstanding
+    aers: u16,
+    // MI v2.0, 5.3, Figure 97: CSTS events held until a CCF poll clears them
+    csts_latched: FlagSet<nvme::mi::ControllerStatusFlags>,
+    virt: ControllerVirtualization,
+    pcie: pcie::PciDeviceFunctionConfigurationSpace,
+    frs: heapless::Vec<&'static str, MAX_FIRMWARE_SLOTS>,
+    afi: u8,
+    dst: DeviceSelfTest,
+    kat: KeepAliveTimer,
+    // Base v2.1, 5.1.13.2.1, Figure 312: In units of CAP.MPSMIN, 0 is unlimited
+    mdts: u8,
+}
+
+// Base v2.1, 5.1.12.1.6, Figure 210
+#[derive(Clone, Copy, Debug)]
+struct DeviceSelfTestEntry {
+    stc: nvme::DeviceSelfTestCode,
+    result: nvme::DeviceSelfTestResult,
+    sn: Option<u8>,
+    nsid: Option<NamespaceId>,
+}
+
+// Base v2.1, 5.1.12.1.6
+#[derive(Debug)]
+struct DeviceSelfTest {
+    current: Option<(nvme::DeviceSelfTestCode, Option<NamespaceId>)>,
+    completion: u8,
+    // Most recent result first
+    results: heapless::Deque<DeviceSelfTestEntry, MAX_DEVICE_SELF_TEST_RESULTS>,
 }
 
+// Base v2.1, 3.9
 #[derive(Debug)]
+struct KeepAliveTimer {
+    // Milliseconds, with 0 disabling the timer
+    kato: u32,
+    elapsed: u32,
+}
+
+impl KeepAliveTimer {
+    fn new() -> Self {
+        Self {
+            kato: 0,
+            elapsed: 0,
+        }
+    }
+}
+
+impl DeviceSelfTest {
+    fn new() -> Self {
+        Self {
+            current: None,
+            completion: 0,
+            results: heapless::Deque::new(),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
 pub enum ControllerError {
-    NamespaceAlreadyAttached,
-    NamespaceNotAttached,
-    NamespaceAttachmentLimitExceeded,
+    CapacityInvalid,
+    DeviceSelfTestInProgress,
+    DeviceSelfTestNotInProgress,
+    FirmwareSlotInvalid,
+    FirmwareSlotLimitExceeded,
+    PcieFunctionInvalid,
+    QueueCountInvalid,
+    SecondaryControllerInvalid,
+    SecondaryControllerLimitExceeded,
+    SpareThresholdInvalid,
+    TemperatureThresholdInvalid,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum NamespaceAttachmentError {
+    AlreadyAttached,
+    NotAttached,
+    LimitExceeded,
 }
 
-impl Controller {
-    fn new(id: ControllerId, port: PortId) -> Self {
+impl<const NSS: usize> GenericController<NSS> {
+    fn new(
+        id: ControllerId,
+        port: PortId,
+        pcie: pcie::PciDeviceFunctionConfigurationSpace,
+        fr: &'static str,
+    ) -> Self {
         Self {
             id,
             cntrltype: ControllerType::Io,
             port,
+            func: 0,
             secondaries: heapless::Vec::new(),
             active_ns: heapless::Vec::new(),
             temp: 293,
             temp_range: OperatingRange::new(UnitKind::Kelvin, 213, 400),
+            wctemp: 0x157,
+            cctemp: 0x157,
+            tmpth: OperatingRange::new(
+                UnitKind::Kelvin,
+                DEFAULT_UNDER_TEMPERATURE_THRESHOLD,
+                DEFAULT_OVER_TEMPERATURE_THRESHOLD,
+            ),
             capacity: 100,
             spare: 100,
             spare_range: OperatingRange::new(UnitKind::Percent, 5, 100),
             write_age: 38,
             write_lifespan: 100,
+            rd: false,
             ro: false,
+            vmbf: false,
+            pmrro: false,
             cc: nvme::ControllerConfiguration::default(),
             csts: FlagSet::empty(),
             lpa: FlagSet::empty(),
+            oacs: FlagSet::empty(),
+            acl: 0,
+            aerl: 0,
+            nsqa: 0,
+            ncqa: 0,
             lsaes: {
                 let mut arr = [FlagSet::default(); 130];
                 arr[AdminGetLogPageLidRequestType::SupportedLogPages.id() as usize] =
                     LidSupportedAndEffectsFlags::Lsupp.into();
                 arr[AdminGetLogPageLidRequestType::SmartHealthInformation.id() as usize] =
                     LidSupportedAndEffectsFlags::Lsupp.into();
+                arr[AdminGetLogPageLidRequestType::FirmwareSlotInformation.id() as usize] =
+                    LidSupportedAndEffectsFlags::Lsupp.into();
+                arr[AdminGetLogPageLidRequestType::ChangedNamespaceList.id() as usize] =
+                    LidSupportedAndEffectsFlags::Lsupp.into();
+                arr[AdminGetLogPageLidRequestType::CommandsSupportedAndEffects.id() as usize] =
+                    LidSupportedAndEffectsFlags::Lsupp.into();
+                arr[AdminGetLogPageLidRequestType::DeviceSelfTest.id() as usize] =
+                    LidSupportedAndEffectsFlags::Lsupp.into();
                 arr[AdminGetLogPageLidRequestType::FeatureIdentifiersSupportedAndEffects.id()
                     as usize] = LidSupportedAndEffectsFlags::Lsupp.into();
                 arr[AdminGetLogPageLidRequestType::SanitizeStatus.id() as usize] =
                     LidSupportedAndEffectsFlags::Lsupp.into();
                 arr
             },
+            // The admin commands implemented over NVMe-MI
+            acses: {
+                use CommandSupportedAndEffectsFlags as F;
+                let mut arr = [FlagSet::default(); 256];
+                arr[0x02] = F::Csupp.into(); // Get Log Page
+                arr[0x06] = F::Csupp.into(); // Identify
+                arr[0x08] = F::Csupp.into(); // Abort
+                arr[0x09] = F::Csupp.into(); // Set Features
+                arr[0x0a] = F::Csupp.into(); // Get Features
+                arr[0x0c] = F::Csupp.into(); // Asynchronous Event Request
+                arr[0x0d] = F::Csupp | F::Ncc | F::Nic; // Namespace Management
+                arr[0x15] = F::Csupp | F::Nic; // Namespace Attachment
+                arr[0x18] = F::Csupp.into(); // Keep Alive
+                arr[0x1c] = F::Csupp | F::Ccc; // Virtualization Management
+                arr[0x80] = F::Csupp | F::Lbcc | F::Ncc; // Format NVM
+                arr[0x84] = F::Csupp | F::Lbcc; // Sanitize
+                arr
+            },
             fna: (nvme::FormatNvmAttributes::Fns
                 | nvme::FormatNvmAttributes::Sens
                 | nvme::FormatNvmAttributes::Fnvmbs),
+            chns: ChangedNamespaceList::new(),
+            iocs_supported: nvme::IoCommandSetFlags::Nvm.into(),
+            iocs_enabled: nvme::IoCommandSetFlags::Nvm.into(),
+            cwarn_latch: nvme::mi::CriticalWarningFlags::St.into(),
+            cwarn_latched: FlagSet::empty(),
+            aec: FlagSet::empty(),
+            cwarn_asserted: FlagSet::empty(),
+            aens: FlagSet::empty(),
+            aers: 0,
+            csts_latched: FlagSet::empty(),
+            virt: ControllerVirtualization::new(),
+            pcie,
+            frs: heapless::Vec::from_slice(&[fr]).unwrap(),
+            afi: 1,
+            dst: DeviceSelfTest::new(),
+            kat: KeepAliveTimer::new(),
+            mdts: 0,
         }
     }
 
@@ -385,73 +963,575 @@ impl Controller {
                 } else {
                     self.csts -= nvme::ControllerStatusFlags::Rdy;
                 }
+                // Shutdown processing completes immediately
+                if self.cc.shn == nvme::ShutdownNotification::None {
+                    self.csts -= nvme::ControllerStatusFlags::ShstReserved;
+                } else {
+                    self.csts -= nvme::ControllerStatusFlags::ShstInProgress;
+                    self.csts |= nvme::ControllerStatusFlags::ShstComplete;
+                }
             }
         }
     }
 
+    // Base v2.1, 3.7.2, Controller Level Reset
+    pub fn reset(&mut self) {
+        debug!("Resetting CTLRID {}", self.id.0);
+        self.set_property(nvme::ControllerProperties::Cc(Default::default()));
+        self.csts -= nvme::ControllerStatusFlags::Cfs;
+        // Outstanding commands are aborted by the reset
+        self.aers = 0;
+    }
+
+    pub fn set_log_page_attributes(&mut self, lpa: FlagSet<LogPageAttributes>) {
+        self.lpa = lpa;
+    }
+
+    pub fn set_controller_type(&mut self, cntrltype: ControllerType) {
+        self.cntrltype = cntrltype;
+    }
+
+    pub fn set_optional_admin_command_support(
+        &mut self,
+        oacs: FlagSet<nvme::OptionalAdminCommandSupport>,
+    ) {
+        self.oacs = oacs;
+    }
+
+    pub fn set_supported_command_sets(&mut self, iocs: FlagSet<nvme::IoCommandSetFlags>) {
+        self.iocs_supported = iocs;
+        self.iocs_enabled &= iocs;
+    }
+
+    pub fn set_enabled_command_sets(&mut self, iocs: FlagSet<nvme::IoCommandSetFlags>) {
+        self.iocs_enabled = iocs & self.iocs_supported;
+    }
+
+    pub fn set_abort_command_limit(&mut self, acl: u8) {
+        self.acl = acl;
+    }
+
+    pub fn set_async_event_request_limit(&mut self, aerl: u8) {
+        self.aerl = aerl;
+    }
+
+    // 0's based counts of I/O submission and completion queues
+    pub fn set_number_of_queues(&mut self, nsqa: u16, ncqa: u16) -> Result<(), ControllerError> {
+        // Base v2.1, 5.1.25, Number of Queues: FFFFh is invalid
+        if nsqa == u16::MAX || ncqa == u16::MAX {
+            return Err(ControllerError::QueueCountInvalid);
+        }
+
+        self.nsqa = nsqa;
+        self.ncqa = ncqa;
+        Ok(())
+    }
+
     pub fn set_temperature(&mut self, temp: Temperature<u16>) {
         let Temperature::Kelvin(k) = temp else {
             todo!("Support units other than kelvin");
         };
 
         self.temp = k;
+        self.latch_cwarn();
     }
 
-    pub fn attach_namespace(&mut self, nsid: NamespaceId) -> Result<(), ControllerError> {
-        debug!("Attaching NSID {} to CTLRID {}", nsid.0, self.id.0);
-        if self.active_ns.iter().any(|ns| ns.0 == nsid.0) {
-            return Err(ControllerError::NamespaceAlreadyAttached);
-        }
+    pub fn set_temperature_thresholds(
+        &mut self,
+        warning: Temperature<u16>,
+        critical: Temperature<u16>,
+    ) -> Result<(), ControllerError> {
+        let (Some(wctemp), Some(cctemp)) = (
+            self.tmpth.temperature(warning),
+            self.tmpth.temperature(critical),
+        ) else {
+            return Err(ControllerError::TemperatureThresholdInvalid);
+        };
 
-        if self.active_ns.push(nsid).is_err() {
-            return Err(ControllerError::NamespaceAttachmentLimitExceeded);
+        if wctemp <= self.tmpth.lower || wctemp > cctemp {
+            return Err(ControllerError::TemperatureThresholdInvalid);
         }
 
+        // The critical threshold becomes the over temperature threshold
+        self.set_over_tmpth(cctemp)?;
+        self.wctemp = wctemp;
+        self.cctemp = cctemp;
         Ok(())
     }
 
-    pub fn detach_namespace(&mut self, nsid: NamespaceId) -> Result<(), ControllerError> {
-        debug!("Detaching NSID {} from CTRLID {}", nsid.0, self.id.0);
-        let Some((idx, _)) = self
-            .active_ns
-            .iter()
-            .enumerate()
-            .find(|args| args.1.0 == nsid.0)
-        else {
-            return Err(ControllerError::NamespaceNotAttached);
+    pub fn set_under_temperature_threshold(
+        &mut self,
+        threshold: Temperature<u16>,
+    ) -> Result<(), ControllerError> {
+        let Some(tmpth) = self.tmpth.temperature(threshold) else {
+            return Err(ControllerError::TemperatureThresholdInvalid);
         };
 
-        let _ = self.active_ns.swap_remove(idx);
+        self.set_under_tmpth(tmpth)
+    }
+
+    fn set_over_tmpth(&mut self, tmpth: u16) -> Result<(), ControllerError> {
+        if tmpth <= self.tmpth.lower {
+            return Err(ControllerError::TemperatureThresholdInvalid);
+        }
 
+        self.tmpth.upper = tmpth;
+        self.latch_cwarn();
         Ok(())
     }
-}
 
-#[derive(Debug)]
-struct SubsystemHealth {
-    nss: nvme::mi::NvmSubsystemStatus,
-}
-
-impl SubsystemHealth {
-    fn new() -> Self {
-        Self {
-            nss: nvme::mi::NvmSubsystemStatus::new(),
+    fn set_under_tmpth(&mut self, tmpth: u16) -> Result<(), ControllerError> {
+        // The under temperature threshold must sit below both the warning and over
+        // temperature thresholds
+        if tmpth >= self.tmpth.upper || (self.wctemp != 0 && tmpth >= self.wctemp) {
+            return Err(ControllerError::TemperatureThresholdInvalid);
         }
+
+        self.tmpth.lower = tmpth;
+        self.latch_cwarn();
+        Ok(())
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-pub enum NamespaceIdentifierType {
-    Ieuid([u8; 8]),
-    Nguid([u8; 16]),
-    Nuuid(Uuid),
-    Csi(nvme::CommandSetIdentifier),
-}
+    // Base v2.1, 5.1.13.2.1, WCTEMP
+    pub fn temperature_warning(&self) -> bool {
+        self.wctemp != 0 && self.temp >= self.wctemp
+    }
 
-// Base v2.1, 3.2.1
-// Base v2.1, 3.2.1.5, Figure 71
-#[derive(Clone, Copy, Debug)]
-enum NamespaceIdDisposition<'a> {
+    pub fn set_capacity(&mut self, capacity: u64) -> Result<(), ControllerError> {
+        // The spare is reported as a percentage of the capacity
+        if capacity == 0 {
+            return Err(ControllerError::CapacityInvalid);
+        }
+
+        self.capacity = capacity;
+        self.latch_cwarn();
+        Ok(())
+    }
+
+    // In the same units as the capacity
+    pub fn set_spare(&mut self, spare: u64) {
+        self.spare = spare;
+        self.latch_cwarn();
+    }
+
+    pub fn set_spare_threshold(&mut self, percent: u8) -> Result<(), ControllerError> {
+        // Base v2.1, 5.1.12.1.3, Figure 206, AVSPT: Values above 100 are reserved
+        if percent > self.spare_range.upper {
+            return Err(ControllerError::SpareThresholdInvalid);
+        }
+
+        self.spare_range.lower = percent;
+        self.latch_cwarn();
+        Ok(())
+    }
+
+    // Base v2.1, 5.1.12.1.3, Figure 206, AVSP
+    fn spare_percent(&self) -> Option<u8> {
+        // Widen to avoid overflow for capacities near u64::MAX
+        (100 * self.spare as u128)
+            .checked_div(self.capacity as u128)
+            .map(|spare| spare.min(100) as u8)
+    }
+
+    pub fn set_reliability_degraded(&mut self, rd: bool) {
+        self.rd = rd;
+        self.latch_cwarn();
+    }
+
+    pub fn set_read_only(&mut self, ro: bool) {
+        self.ro = ro;
+        self.latch_cwarn();
+    }
+
+    pub fn set_volatile_memory_backup_failed(&mut self, vmbf: bool) {
+        self.vmbf = vmbf;
+        self.latch_cwarn();
+    }
+
+    pub fn set_persistent_memory_read_only(&mut self, pmrro: bool) {
+        self.pmrro = pmrro;
+        self.latch_cwarn();
+    }
+
+    pub fn cwarn_latch(&self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        self.cwarn_latch
+    }
+
+    pub fn set_cwarn_latch(&mut self, latch: FlagSet<nvme::mi::CriticalWarningFlags>) {
+        self.cwarn_latch = latch;
+        self.cwarn_latched &= latch;
+        self.latch_cwarn();
+    }
+
+    pub fn acknowledge_cwarn(&mut self, ack: FlagSet<nvme::mi::CriticalWarningFlags>) {
+        self.cwarn_latched -= ack;
+        // Warnings whose conditions persist remain latched
+        self.latch_cwarn();
+    }
+
+    pub fn async_event_configuration(&self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        self.aec
+    }
+
+    // Critical warnings asserted since the previous call and enabled by AEC
+    pub fn take_async_events(&mut self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        core::mem::take(&mut self.aens)
+    }
+
+    fn cwarn_conditions(&self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        let mut fs = FlagSet::empty();
+
+        if self
+            .spare_percent()
+            .is_some_and(|spare| spare < self.spare_range.lower)
+        {
+            fs |= nvme::mi::CriticalWarningFlags::St;
+        }
+
+        // Only the temperature thresholds raise the temperature critical warning. Reaching
+        // WCTEMP is reported by temperature_warning()
+        if self.temp <= self.tmpth.lower || self.temp >= self.tmpth.upper {
+            fs |= nvme::mi::CriticalWarningFlags::Taut;
+        }
+
+        if self.rd {
+            fs |= nvme::mi::CriticalWarningFlags::Rd;
+        }
+
+        if self.ro {
+            fs |= nvme::mi::CriticalWarningFlags::Ro;
+        }
+
+        if self.vmbf {
+            fs |= nvme::mi::CriticalWarningFlags::Vmbf;
+        }
+
+        if self.pmrro {
+            fs |= nvme::mi::CriticalWarningFlags::Pmre;
+        }
+
+        fs
+    }
+
+    pub fn set_telemetry_data_available(&mut self) {
+        self.csts_latched |= nvme::mi::ControllerStatusFlags::Tcida;
+    }
+
+    fn latch_cwarn(&mut self) {
+        let conditions = self.cwarn_conditions();
+        self.cwarn_latched |= conditions & self.cwarn_latch;
+        self.aens |= (conditions - self.cwarn_asserted) & self.aec;
+        self.cwarn_asserted = conditions;
+    }
+
+    fn cwarn(&self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        self.cwarn_conditions() | self.cwarn_latched
+    }
+
+    pub fn pcie_configuration_space(&self) -> &pcie::PciDeviceFunctionConfigurationSpace {
+        &self.pcie
+    }
+
+    pub fn pcie_configuration_space_mut(
+        &mut self,
+    ) -> &mut pcie::PciDeviceFunctionConfigurationSpace {
+        &mut self.pcie
+    }
+
+    pub fn set_pcie_configuration_space(
+        &mut self,
+        pcie: pcie::PciDeviceFunctionConfigurationSpace,
+    ) {
+        self.pcie = pcie;
+    }
+
+    pub fn firmware_slots(&self) -> impl Iterator<Item = &&'static str> {
+        self.frs.iter()
+    }
+
+    // Base v2.1, 5.1.12.1.4: Slots are numbered from 1, empty if without firmware
+    pub fn set_firmware_slots(&mut self, frs: &[&'static str]) -> Result<(), ControllerError> {
+        if frs.is_empty() {
+            return Err(ControllerError::FirmwareSlotInvalid);
+        }
+
+        self.frs = heapless::Vec::from_slice(frs)
+            .map_err(|_| ControllerError::FirmwareSlotLimitExceeded)?;
+        if self.afi as usize > self.frs.len() {
+            self.afi = 1;
+        }
+        Ok(())
+    }
+
+    pub fn active_firmware_slot(&self) -> u8 {
+        self.afi
+    }
+
+    pub fn set_active_firmware_slot(&mut self, slot: u8) -> Result<(), ControllerError> {
+        if slot == 0 || slot as usize > self.frs.len() {
+            return Err(ControllerError::FirmwareSlotInvalid);
+        }
+
+        if slot != self.afi {
+            self.csts_latched |= nvme::mi::ControllerStatusFlags::Fa;
+        }
+
+        self.afi = slot;
+        Ok(())
+    }
+
+    pub fn pcie_function(&self) -> u8 {
+        self.func
+    }
+
+    pub fn set_pcie_function(&mut self, func: u8) -> Result<(), ControllerError> {
+        // Without ARI the function number is limited to three bits
+        if func > 7 {
+            return Err(ControllerError::PcieFunctionInvalid);
+        }
+
+        self.func = func;
+        Ok(())
+    }
+
+    fn firmware_revision(&self) -> &'static str {
+        self.frs[self.afi as usize - 1]
+    }
+
+    pub fn start_device_self_test(
+        &mut self,
+        stc: nvme::DeviceSelfTestCode,
+        nsid: Option<NamespaceId>,
+    ) -> Result<(), ControllerError> {
+        if self.dst.current.is_some() {
+            return Err(ControllerError::DeviceSelfTestInProgress);
+        }
+
+        self.dst.current = Some((stc, nsid));
+        self.dst.completion = 0;
+        Ok(())
+    }
+
+    pub fn set_device_self_test_completion(&mut self, percent: u8) -> Result<(), ControllerError> {
+        if self.dst.current.is_none() {
+            return Err(ControllerError::DeviceSelfTestNotInProgress);
+        }
+
+        self.dst.completion = percent.min(100);
+        Ok(())
+    }
+
+    pub fn complete_device_self_test(
+        &mut self,
+        result: nvme::DeviceSelfTestResult,
+        segment: Option<u8>,
+    ) -> Result<(), ControllerError> {
+        let Some((stc, nsid)) = self.dst.current.take() else {
+            return Err(ControllerError::DeviceSelfTestNotInProgress);
+        };
+
+        // Base v2.1, 5.1.12.1.6: Only the 20 most recent results are retained
+        if self.dst.results.is_full() {
+            self.dst.results.pop_back();
+        }
+
+        let entry = DeviceSelfTestEntry {
+            stc,
+            result,
+            sn: segment,
+            nsid,
+        };
+        // Cannot fail as space was made above
+        let _ = self.dst.results.push_front(entry);
+        self.dst.completion = 0;
+        Ok(())
+    }
+
+    pub fn max_data_transfer_size(&self) -> u8 {
+        self.mdts
+    }
+
+    pub fn set_max_data_transfer_size(&mut self, mdts: u8) {
+        self.mdts = mdts;
+    }
+
+    // Limits beyond 2^20 minimum-sized pages exceed any u32 length
+    fn transfer_permitted(&self, len: u32) -> bool {
+        self.mdts == 0 || u64::from(len) <= MIN_MEMORY_PAGE_SIZE << self.mdts.min(20)
+    }
+
+    pub fn keep_alive_timeout(&self) -> u32 {
+        self.kat.kato
+    }
+
+    pub fn set_keep_alive_timeout(&mut self, kato: u32) {
+        self.kat.kato = kato;
+        self.kat.elapsed = 0;
+    }
+
+    pub fn keep_alive(&mut self) {
+        self.kat.elapsed = 0;
+    }
+
+    pub fn advance_keep_alive_timer(&mut self, ms: u32) {
+        self.kat.elapsed = self.kat.elapsed.saturating_add(ms);
+    }
+
+    pub fn keep_alive_expired(&self) -> bool {
+        self.kat.kato != 0 && self.kat.elapsed >= self.kat.kato
+    }
+
+    pub fn id(&self) -> ControllerId {
+        self.id
+    }
+
+    pub fn port(&self) -> PortId {
+        self.port
+    }
+
+    pub fn active_namespaces(&self) -> impl Iterator<Item = &NamespaceId> {
+        self.active_ns.iter()
+    }
+
+    pub fn attach_namespace(&mut self, nsid: NamespaceId) -> Result<(), NamespaceAttachmentError> {
+        debug!("Attaching NSID {} to CTLRID {}", nsid.0, self.id.0);
+        if self.active_ns.iter().any(|ns| ns.0 == nsid.0) {
+            return Err(NamespaceAttachmentError::AlreadyAttached);
+        }
+
+        if self.active_ns.push(nsid).is_err() {
+            return Err(NamespaceAttachmentError::LimitExceeded);
+        }
+
+        self.chns.record(nsid);
+        self.csts_latched |= nvme::mi::ControllerStatusFlags::Nac;
+
+        Ok(())
+    }
+
+    pub fn add_secondary(
+        &mut self,
+        scid: u16,
+        vfn: u16,
+        nvq: u16,
+        nvi: u16,
+    ) -> Result<(), ControllerError> {
+        debug!("Adding secondary CTLRID {} to CTLRID {}", scid, self.id.0);
+        // Virtual function numbers start at 1
+        if scid == self.id.0 || vfn == 0 {
+            return Err(ControllerError::SecondaryControllerInvalid);
+        }
+
+        if self
+            .secondaries
+            .iter()
+            .any(|sc| sc.id.0 == scid || sc.vfn == vfn)
+        {
+            return Err(ControllerError::SecondaryControllerInvalid);
+        }
+
+        if nvq > self.virt.vq.frsm || nvi > self.virt.vi.frsm {
+            return Err(ControllerError::SecondaryControllerInvalid);
+        }
+
+        self.secondaries
+            .push(SecondaryController {
+                id: ControllerId(scid),
+                vfn,
+                nvq,
+                nvi,
+                online: false,
+            })
+            .map_err(|_| ControllerError::SecondaryControllerLimitExceeded)
+    }
+
+    pub fn detach_namespace(&mut self, nsid: NamespaceId) -> Result<(), NamespaceAttachmentError> {
+        debug!("Detaching NSID {} from CTRLID {}", nsid.0, self.id.0);
+        let Some((idx, _)) = self
+            .active_ns
+            .iter()
+            .enumerate()
+            .find(|args| args.1.0 == nsid.0)
+        else {
+            return Err(NamespaceAttachmentError::NotAttached);
+        };
+
+        let _ = self.active_ns.swap_remove(idx);
+
+        self.chns.record(nsid);
+        self.csts_latched |= nvme::mi::ControllerStatusFlags::Nac;
+
+        Ok(())
+    }
+}
+
+// Worst-case health across all controllers in the subsystem
+#[derive(Clone, Copy, Debug)]
+pub struct AggregateHealth {
+    // Kelvin, clamped to the reporting controller's operating range
+    temp: u16,
+    // Percent
+    spare: u8,
+    // Percent
+    pldu: u8,
+    cwarn: FlagSet<nvme::mi::CriticalWarningFlags>,
+}
+
+impl AggregateHealth {
+    pub fn temperature(&self) -> Temperature<u16> {
+        Temperature::Kelvin(self.temp)
+    }
+
+    pub fn spare(&self) -> u8 {
+        self.spare
+    }
+
+    pub fn pldu(&self) -> u8 {
+        self.pldu
+    }
+
+    pub fn cwarn(&self) -> FlagSet<nvme::mi::CriticalWarningFlags> {
+        self.cwarn
+    }
+}
+
+#[derive(Debug)]
+struct SubsystemHealth {
+    nss: nvme::mi::NvmSubsystemStatus,
+}
+
+impl SubsystemHealth {
+    fn new() -> Self {
+        Self {
+            nss: nvme::mi::NvmSubsystemStatus::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum NamespaceIdentifierType {
+    Ieuid([u8; 8]),
+    Nguid([u8; 16]),
+    Nuuid(Uuid),
+    Csi(nvme::CommandSetIdentifier),
+}
+
+impl NamespaceIdentifierType {
+    // Base v2.1, 5.1.13.2.3, Figure 315
+    fn nidt(&self) -> u8 {
+        match self {
+            Self::Ieuid(_) => 1,
+            Self::Nguid(_) => 2,
+            Self::Nuuid(_) => 3,
+            Self::Csi(_) => 4,
+        }
+    }
+}
+
+// Base v2.1, 3.2.1
+// Base v2.1, 3.2.1.5, Figure 71
+#[derive(Clone, Copy, Debug)]
+enum NamespaceIdDisposition<'a> {
     Invalid,
     Broadcast,
     Unallocated,
@@ -464,7 +1544,18 @@ enum NamespaceIdDisposition<'a> {
 pub struct NamespaceId(u32);
 
 impl NamespaceId {
-    fn disposition<'a>(&self, subsys: &'a Subsystem) -> NamespaceIdDisposition<'a> {
+    pub const fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    pub const fn value(&self) -> u32 {
+        self.0
+    }
+
+    fn disposition<'a, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        subsys: &'a GenericSubsystem<PORTS, CTLRS, NSS>,
+    ) -> NamespaceIdDisposition<'a> {
         if self.0 == 0 {
             return NamespaceIdDisposition::Invalid;
         }
@@ -473,8 +1564,7 @@ impl NamespaceId {
             return NamespaceIdDisposition::Broadcast;
         }
 
-        assert!(subsys.nss.capacity() <= u32::MAX.try_into().unwrap());
-        if self.0 > subsys.nss.capacity() as u32 {
+        if self.0 > Self::max(subsys) {
             return NamespaceIdDisposition::Invalid;
         }
 
@@ -482,19 +1572,23 @@ impl NamespaceId {
             return NamespaceIdDisposition::Unallocated;
         };
 
-        if !subsys
-            .ctlrs
-            .iter()
-            .flat_map(|c| c.active_ns.iter())
-            .any(|&nsid| nsid.0 == self.0)
-        {
+        if !subsys.namespace_attached(*self) {
             return NamespaceIdDisposition::Inactive(ns);
         }
 
         NamespaceIdDisposition::Active(ns)
     }
 
-    fn max(subsys: &Subsystem) -> u32 {
+    // Base v2.1, 5.1.13.2.1, Figure 312, NN: NSIDs are not reused
+    fn max<const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        subsys: &GenericSubsystem<PORTS, CTLRS, NSS>,
+    ) -> u32 {
+        Self::capacity(subsys).max(subsys.nsids)
+    }
+
+    fn capacity<const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        subsys: &GenericSubsystem<PORTS, CTLRS, NSS>,
+    ) -> u32 {
         subsys
             .nss
             .capacity()
@@ -510,7 +1604,15 @@ pub struct Namespace {
     capacity: u64,
     used: u64,
     block_order: u8,
-    nids: [NamespaceIdentifierType; 2],
+    // Ordered by NIDT
+    nids: heapless::Vec<NamespaceIdentifierType, MAX_NIDTS>,
+    maxcna: u16,
+    private: bool,
+    // Base v2.1, 5.1.12.1.3: Data units are counted in 512 byte granularity
+    rd_units: u128,
+    wr_units: u128,
+    hrc: u128,
+    hwc: u128,
 }
 
 impl Namespace {
@@ -522,25 +1624,129 @@ impl Namespace {
         uuid::Builder::from_random_bytes(digest).into_uuid()
     }
 
+    // Capacity in 512-byte logical blocks
     pub fn new(nsid: NamespaceId, uuid: Uuid, capacity: u64) -> Self {
         Self {
             id: nsid,
             size: capacity,
             capacity,
             used: 0,
-            block_order: 9,
-            nids: [
+            block_order: MIN_BLOCK_ORDER,
+            nids: heapless::Vec::from_slice(&[
                 NamespaceIdentifierType::Nuuid(uuid),
                 NamespaceIdentifierType::Csi(nvme::CommandSetIdentifier::Nvm),
-            ],
+            ])
+            .unwrap(),
+            maxcna: u16::MAX,
+            private: false,
+            rd_units: 0,
+            wr_units: 0,
+            hrc: 0,
+            hwc: 0,
+        }
+    }
+
+    pub fn set_max_attached_controllers(&mut self, maxcna: u16) {
+        self.maxcna = maxcna;
+    }
+
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+    }
+
+    pub fn set_eui64(&mut self, eui64: [u8; 8]) {
+        self.set_nid(NamespaceIdentifierType::Ieuid(eui64));
+    }
+
+    pub fn set_nguid(&mut self, nguid: [u8; 16]) {
+        self.set_nid(NamespaceIdentifierType::Nguid(nguid));
+    }
+
+    fn set_nid(&mut self, nid: NamespaceIdentifierType) {
+        let pos = self.nids.iter().position(|v| v.nidt() >= nid.nidt());
+        match pos {
+            Some(idx) if self.nids[idx].nidt() == nid.nidt() => self.nids[idx] = nid,
+            // MAX_NIDTS accommodates every NIDT
+            Some(idx) => self.nids.insert(idx, nid).unwrap(),
+            None => self.nids.push(nid).unwrap(),
+        }
+    }
+
+    // The allocated NVM is unchanged, so size and capacity are rescaled
+    pub fn set_lba_format(&mut self, block_order: u8) -> Result<(), NamespaceError> {
+        if !(MIN_BLOCK_ORDER..=MAX_BLOCK_ORDER).contains(&block_order) {
+            debug!("Unsupported block order: {block_order}");
+            return Err(NamespaceError::LbaFormatInvalid);
         }
+
+        let rescale = |blocks: u64| (((blocks as u128) << self.block_order) >> block_order) as u64;
+        self.size = rescale(self.size);
+        self.capacity = rescale(self.capacity);
+        self.used = rescale(self.used);
+        self.block_order = block_order;
+        Ok(())
+    }
+
+    pub fn record_read(&mut self, blocks: u64) {
+        self.hrc = self.hrc.saturating_add(1);
+        self.rd_units = self
+            .rd_units
+            .saturating_add((blocks as u128) << (self.block_order - MIN_BLOCK_ORDER));
+    }
+
+    pub fn record_write(&mut self, blocks: u64) {
+        self.hwc = self.hwc.saturating_add(1);
+        self.wr_units = self
+            .wr_units
+            .saturating_add((blocks as u128) << (self.block_order - MIN_BLOCK_ORDER));
+    }
+
+    pub fn uuid(&self) -> Option<Uuid> {
+        self.nids.iter().find_map(|nid| match nid {
+            NamespaceIdentifierType::Nuuid(uuid) => Some(*uuid),
+            _ => None,
+        })
+    }
+
+    fn csi(&self) -> nvme::CommandSetIdentifier {
+        self.nids
+            .iter()
+            .find_map(|nid| match nid {
+                NamespaceIdentifierType::Csi(csi) => Some(*csi),
+                _ => None,
+            })
+            .unwrap_or(nvme::CommandSetIdentifier::Nvm)
+    }
+
+    pub fn id(&self) -> NamespaceId {
+        self.id
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum NamespaceError {
+    LbaFormatInvalid,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum SubsystemError {
     ControllerLimitExceeded,
+    MiVersionUnsupported,
     NamespaceIdentifierUnavailable,
+    NamespaceUseExceedsCapacity,
+    PortIdentifierUnavailable,
+    PortMtuInvalid,
+    SanitizeInProgress,
+    SanitizeNotInProgress,
+    SubsystemNqnInvalid,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -553,42 +1759,98 @@ pub struct SubsystemInfo {
     pub instance: [u8; 16],
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum SubsystemInfoError {
+    IeeeOuiInvalid,
+    PciVidInvalid,
+    PciDidInvalid,
+    PciSvidInvalid,
+    PciSdidInvalid,
+    SourceDateEpochInvalid,
+}
+
 impl SubsystemInfo {
-    fn acquire_source_date_epoch() -> u64 {
-        env!("SOURCE_DATE_EPOCH").parse::<u64>().unwrap_or(0)
+    fn acquire_source_date_epoch(sde: &str) -> Result<u64, SubsystemInfoError> {
+        sde.parse::<u64>().map_err(|_| {
+            debug!("Malformed SOURCE_DATE_EPOCH '{sde}'");
+            SubsystemInfoError::SourceDateEpochInvalid
+        })
     }
 
-    fn acquire_ieee_oui() -> [u8; 3] {
-        let mut oui = [0u8; 3];
+    fn acquire_ieee_oui(oui: Option<&str>) -> Result<[u8; 3], SubsystemInfoError> {
         // ac-de-48 is allocated as private, used as the example value in the
         // IEEE Guidelines for use of EUI, OUI, and CID documentation
-        for (idx, val) in option_env!("NVME_MI_DEV_IEEE_OUI")
-            .unwrap_or("ac-de-48")
-            .split('-')
-            .take(oui.len())
-            .map(|v| {
-                u8::from_str_radix(v, 16).expect(
-                    "NVME_MI_DEV_IEEE_OUI must be set in the IEEE RA hexadecimal representation",
-                )
-            })
-            .enumerate()
-        {
-            oui[idx] = val;
+        let Some(oui) = oui else {
+            return Ok([0xac, 0xde, 0x48]);
+        };
+        Self::parse_ieee_oui(oui).ok_or_else(|| {
+            debug!("Malformed NVME_MI_DEV_IEEE_OUI '{oui}'");
+            SubsystemInfoError::IeeeOuiInvalid
+        })
+    }
+
+    // The IEEE RA hexadecimal representation, e.g. "ac-de-48"
+    fn parse_ieee_oui(oui: &str) -> Option<[u8; 3]> {
+        let mut res = [0u8; 3];
+        let mut octets = oui.split('-');
+        for v in res.iter_mut() {
+            let octet = octets.next()?;
+            if octet.len() != 2 || !octet.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            *v = u8::from_str_radix(octet, 16).ok()?;
         }
+        octets.next().is_none().then_some(res)
+    }
+
+    // Base v2.1, 4.5.3: Least significant byte first
+    pub fn ieee_oui_wire(&self) -> [u8; 3] {
+        let mut oui = self.ieee_oui;
+        oui.reverse();
         oui
     }
 
-    fn acquire_pci_ids() -> (u16, u16, u16, u16) {
+    fn acquire_pci_ids() -> Result<(u16, u16, u16, u16), SubsystemInfoError> {
+        Ok((
+            Self::acquire_pci_id(
+                option_env!("NVME_MI_DEV_PCI_VID"),
+                SubsystemInfoError::PciVidInvalid,
+            )?,
+            Self::acquire_pci_id(
+                option_env!("NVME_MI_DEV_PCI_DID"),
+                SubsystemInfoError::PciDidInvalid,
+            )?,
+            Self::acquire_pci_id(
+                option_env!("NVME_MI_DEV_PCI_SVID"),
+                SubsystemInfoError::PciSvidInvalid,
+            )?,
+            Self::acquire_pci_id(
+                option_env!("NVME_MI_DEV_PCI_SDID"),
+                SubsystemInfoError::PciSdidInvalid,
+            )?,
+        ))
+    }
+
+    fn acquire_pci_id(
+        id: Option<&str>,
+        err: SubsystemInfoError,
+    ) -> Result<u16, SubsystemInfoError> {
         // ffff is the value returned by an aborted access
-        let vid = u16::from_str_radix(option_env!("NVME_MI_DEV_PCI_VID").unwrap_or("ffff"), 16)
-            .expect("NVME_MI_DEV_PCI_VID must be set to a 16-bit value in base-16 representation");
-        let did = u16::from_str_radix(option_env!("NVME_MI_DEV_PCI_DID").unwrap_or("ffff"), 16)
-            .expect("NVME_MI_DEV_PCI_DID must be set to a 16-bit value in base-16 representation");
-        let svid = u16::from_str_radix(option_env!("NVME_MI_DEV_PCI_SVID").unwrap_or("ffff"), 16)
-            .expect("NVME_MI_DEV_PCI_SVID must be set to a 16-bit value in base-16 representation");
-        let sdid = u16::from_str_radix(option_env!("NVME_MI_DEV_PCI_SDID").unwrap_or("ffff"), 16)
-            .expect("NVME_MI_DEV_PCI_SDID must be set to a 16-bit value in base-16 representation");
-        (vid, did, svid, sdid)
+        let Some(id) = id else {
+            return Ok(0xffff);
+        };
+        Self::parse_pci_id(id).ok_or_else(|| {
+            debug!("Malformed PCI ID '{id}': {err:?}");
+            err
+        })
+    }
+
+    // A 16-bit value in base-16 representation, e.g. "1b96"
+    fn parse_pci_id(id: &str) -> Option<u16> {
+        if id.is_empty() || id.len() > 4 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u16::from_str_radix(id, 16).ok()
     }
 
     pub fn invalid() -> Self {
@@ -602,61 +1864,219 @@ impl SubsystemInfo {
         }
     }
 
-    pub fn environment() -> Self {
-        let (vid, did, svid, sdid) = SubsystemInfo::acquire_pci_ids();
-        let sde = SubsystemInfo::acquire_source_date_epoch().to_le_bytes();
-        let mut instance = [0u8; 16];
-        instance[..sde.len()].copy_from_slice(&sde);
+    pub fn with_ids(
+        vid: u16,
+        did: u16,
+        svid: u16,
+        sdid: u16,
+        oui: [u8; 3],
+        instance: [u8; 16],
+    ) -> Self {
         Self {
             pci_vid: vid,
             pci_did: did,
             pci_svid: svid,
             pci_sdid: sdid,
-            ieee_oui: SubsystemInfo::acquire_ieee_oui(),
+            ieee_oui: oui,
             instance,
         }
     }
+
+    pub fn environment() -> Result<Self, SubsystemInfoError> {
+        let (vid, did, svid, sdid) = SubsystemInfo::acquire_pci_ids()?;
+        let sde =
+            SubsystemInfo::acquire_source_date_epoch(env!("SOURCE_DATE_EPOCH"))?.to_le_bytes();
+        let mut instance = [0u8; 16];
+        instance[..sde.len()].copy_from_slice(&sde);
+        Ok(Self::with_ids(
+            vid,
+            did,
+            svid,
+            sdid,
+            SubsystemInfo::acquire_ieee_oui(option_env!("NVME_MI_DEV_IEEE_OUI"))?,
+            instance,
+        ))
+    }
 }
 
-#[derive(Debug)]
-pub struct Subsystem {
+pub type Subsystem = GenericSubsystem<MAX_PORTS, MAX_CONTROLLERS, MAX_NAMESPACES>;
+
+pub type SubsystemBuilder = GenericSubsystemBuilder<MAX_PORTS, MAX_CONTROLLERS, MAX_NAMESPACES>;
+
+pub struct GenericSubsystemBuilder<const PORTS: usize, const CTLRS: usize, const NSS: usize> {
     info: SubsystemInfo,
     caps: nvme::mi::SubsystemCapabilities,
-    ports: heapless::Vec<Port, MAX_PORTS>,
-    ctlrs: heapless::Vec<Controller, MAX_CONTROLLERS>,
-    nsids: u32,
-    nss: heapless::Vec<Namespace, MAX_NAMESPACES>,
-    health: SubsystemHealth,
-    sanicap: nvme::SanitizeCapabilities,
-    ssi: nvme::SanitizeStateInformation,
-    sstat: nvme::SanitizeStatus,
-    sconf: Option<nvme::AdminSanitizeConfiguration>,
     mi: MiCapability,
     sn: &'static str,
     mn: &'static str,
     fr: &'static str,
 }
 
-impl Subsystem {
+impl<const PORTS: usize, const CTLRS: usize, const NSS: usize>
+    GenericSubsystemBuilder<PORTS, CTLRS, NSS>
+{
     pub fn new(info: SubsystemInfo) -> Self {
-        Subsystem {
+        Self {
             info,
             caps: nvme::mi::SubsystemCapabilities::new(),
+            mi: MiCapability::new(),
+            sn: "1000",
+            mn: "MIDEV",
+            fr: "00.00.01",
+        }
+    }
+
+    pub fn sre(&mut self, sre: bool) -> &mut Self {
+        self.caps.set_sre(sre);
+        self
+    }
+
+    // Checked by build()
+    pub fn mi_version(&mut self, mjr: u8, mnr: u8) -> &mut Self {
+        self.mi = MiCapability { mjr, mnr };
+        self
+    }
+
+    pub fn sn(&mut self, sn: &'static str) -> &mut Self {
+        self.sn = sn;
+        self
+    }
+
+    pub fn mn(&mut self, mn: &'static str) -> &mut Self {
+        self.mn = mn;
+        self
+    }
+
+    pub fn fr(&mut self, fr: &'static str) -> &mut Self {
+        self.fr = fr;
+        self
+    }
+
+    pub fn build(&self) -> Result<GenericSubsystem<PORTS, CTLRS, NSS>, SubsystemError> {
+        let Some(mi) = MiCapability::with_version(self.mi.mjr, self.mi.mnr) else {
+            debug!(
+                "Unsupported NVMe-MI version {}.{}",
+                self.mi.mjr, self.mi.mnr
+            );
+            return Err(SubsystemError::MiVersionUnsupported);
+        };
+
+        Ok(self.assemble(mi))
+    }
+
+    fn assemble(&self, mi: MiCapability) -> GenericSubsystem<PORTS, CTLRS, NSS> {
+        GenericSubsystem {
+            info: self.info,
+            caps: self.caps,
             ports: heapless::Vec::new(),
             ctlrs: heapless::Vec::new(),
             nsids: 0,
             nss: heapless::Vec::new(),
             health: SubsystemHealth::new(),
-            mi: MiCapability::new(),
-            sn: "1000",
-            mn: "MIDEV",
-            fr: "00.00.01",
+            mi,
+            sn: self.sn,
+            mn: self.mn,
+            fr: self.fr,
+            subnqn: None,
             sstat: Default::default(),
+            sprog: u16::MAX,
             sconf: None,
             ssi: Default::default(),
             sanicap: Default::default(),
         }
     }
+}
+
+#[derive(Debug)]
+pub struct GenericSubsystem<const PORTS: usize, const CTLRS: usize, const NSS: usize> {
+    info: SubsystemInfo,
+    caps: nvme::mi::SubsystemCapabilities,
+    ports: heapless::Vec<Port, PORTS>,
+    ctlrs: heapless::Vec<GenericController<NSS>, CTLRS>,
+    nsids: u32,
+    nss: heapless::Vec<Namespace, NSS>,
+    health: SubsystemHealth,
+    sanicap: nvme::SanitizeCapabilities,
+    ssi: nvme::SanitizeStateInformation,
+    sstat: nvme::SanitizeStatus,
+    sprog: u16,
+    sconf: Option<nvme::AdminSanitizeConfiguration>,
+    mi: MiCapability,
+    sn: &'static str,
+    mn: &'static str,
+    fr: &'static str,
+    subnqn: Option<&'static str>,
+}
+
+impl<const PORTS: usize, const CTLRS: usize, const NSS: usize> GenericSubsystem<PORTS, CTLRS, NSS> {
+    pub fn new(info: SubsystemInfo) -> Self {
+        Self::builder(info).assemble(MiCapability::new())
+    }
+
+    pub fn builder(info: SubsystemInfo) -> GenericSubsystemBuilder<PORTS, CTLRS, NSS> {
+        GenericSubsystemBuilder::new(info)
+    }
+
+    pub fn aggregate_health(&self) -> Option<AggregateHealth> {
+        self.ctlrs
+            .iter()
+            .map(|ctlr| AggregateHealth {
+                temp: ctlr
+                    .temp
+                    .clamp(ctlr.temp_range.lower, ctlr.temp_range.upper),
+                spare: ctlr.spare_percent().unwrap_or(0),
+                pldu: core::cmp::min(255, 100 * ctlr.write_age / ctlr.write_lifespan) as u8,
+                cwarn: ctlr.cwarn(),
+            })
+            .reduce(|a, b| AggregateHealth {
+                temp: a.temp.max(b.temp),
+                spare: a.spare.min(b.spare),
+                pldu: a.pldu.max(b.pldu),
+                cwarn: a.cwarn | b.cwarn,
+            })
+    }
+
+    pub fn begin_sanitize(&mut self) -> Result<(), SubsystemError> {
+        if self.sstat.in_progress() {
+            return Err(SubsystemError::SanitizeInProgress);
+        }
+
+        self.ssi = nvme::SanitizeStateInformation::processing();
+        self.sstat.begin();
+        self.sprog = 0;
+        Ok(())
+    }
+
+    pub fn set_sanitize_progress(&mut self, percent: u8) -> Result<(), SubsystemError> {
+        if !self.sstat.in_progress() {
+            return Err(SubsystemError::SanitizeNotInProgress);
+        }
+
+        // Base v2.1, 5.1.12.1.33, Figure 291, SPROG
+        self.sprog = ((u32::from(percent.min(100)) << 16) / 100).min(u16::MAX.into()) as u16;
+        Ok(())
+    }
+
+    pub fn complete_sanitize(&mut self, success: bool) -> Result<(), SubsystemError> {
+        if !self.sstat.in_progress() {
+            return Err(SubsystemError::SanitizeNotInProgress);
+        }
+
+        self.ssi = nvme::SanitizeStateInformation::completed(success);
+        self.sstat.complete(success);
+        self.sprog = u16::MAX;
+        Ok(())
+    }
+
+    pub fn set_mi_version(&mut self, mjr: u8, mnr: u8) -> Result<(), SubsystemError> {
+        self.mi =
+            MiCapability::with_version(mjr, mnr).ok_or(SubsystemError::MiVersionUnsupported)?;
+        Ok(())
+    }
+
+    pub fn set_reset_support(&mut self, sre: bool) {
+        self.caps.set_sre(sre);
+    }
 
     pub fn add_port(&mut self, typ: PortType) -> Result<PortId, Port> {
         debug_assert!(self.ctlrs.len() <= u8::MAX.into());
@@ -664,22 +2084,148 @@ impl Subsystem {
         self.ports.push(p).map(|_p| self.ports.last().unwrap().id)
     }
 
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.ports.iter()
+    }
+
+    pub fn port_mut(&mut self, id: PortId) -> Result<&mut Port, SubsystemError> {
+        self.ports
+            .get_mut(id.0 as usize)
+            .ok_or(SubsystemError::PortIdentifierUnavailable)
+    }
+
+    pub fn set_subnqn(&mut self, subnqn: &'static str) -> Result<(), SubsystemError> {
+        // Base v2.1, 4.7
+        if subnqn.len() > MAX_NQN_LEN || !subnqn.starts_with("nqn.") {
+            debug!("Invalid NQN: {subnqn}");
+            return Err(SubsystemError::SubsystemNqnInvalid);
+        }
+
+        self.subnqn = Some(subnqn);
+        Ok(())
+    }
+
+    pub fn subnqn(&self) -> heapless::String<MAX_NQN_LEN> {
+        let mut nqn = heapless::String::new();
+        match self.subnqn {
+            // Length is validated by set_subnqn()
+            Some(subnqn) => nqn.push_str(subnqn).unwrap(),
+            // Base v2.1, 4.7.1: Derive a UUID-based NQN from the instance data
+            None => write!(
+                nqn,
+                "nqn.2014-08.org.nvmexpress:uuid:{}",
+                uuid::Builder::from_random_bytes(self.info.instance)
+                    .into_uuid()
+                    .hyphenated()
+            )
+            .unwrap(),
+        }
+        nqn
+    }
+
+    pub fn set_port_type(&mut self, id: PortId, typ: PortType) -> Result<(), SubsystemError> {
+        let Some(port) = self.ports.iter_mut().find(|p| p.id == id) else {
+            return Err(SubsystemError::PortIdentifierUnavailable);
+        };
+        debug!("Setting port {} type to {:?}", id.0, typ);
+        port.typ = typ;
+        if let PortType::Pcie(pprt) = &port.typ {
+            for ctlr in self.ctlrs.iter_mut().filter(|c| c.port == id) {
+                ctlr.pcie.set_port(pprt);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_port_inactive(&mut self, id: PortId) -> Result<(), SubsystemError> {
+        self.set_port_type(id, PortType::Inactive)
+    }
+
+    pub fn set_port_mmtus(&mut self, id: PortId, mmtus: u16) -> Result<(), SubsystemError> {
+        let Some(port) = self.ports.iter_mut().find(|p| p.id == id) else {
+            return Err(SubsystemError::PortIdentifierUnavailable);
+        };
+
+        // MI v2.0, 5.7.2, Figure 114: MMTUS is at least the MCTP baseline
+        if mmtus < MCTP_BASELINE_MTU {
+            debug!("Port {} MMTUS {} below MCTP baseline", id.0, mmtus);
+            return Err(SubsystemError::PortMtuInvalid);
+        }
+
+        port.mmtus = mmtus;
+        port.mtus = core::cmp::min(port.mtus, mmtus);
+        Ok(())
+    }
+
+    pub fn set_port_mebs(&mut self, id: PortId, mebs: u32) -> Result<(), SubsystemError> {
+        let Some(port) = self.ports.iter_mut().find(|p| p.id == id) else {
+            return Err(SubsystemError::PortIdentifierUnavailable);
+        };
+        port.mebs = mebs;
+        Ok(())
+    }
+
+    pub fn set_port_capabilities(
+        &mut self,
+        id: PortId,
+        ciaps: bool,
+        aems: bool,
+    ) -> Result<(), SubsystemError> {
+        let Some(port) = self.ports.iter_mut().find(|p| p.id == id) else {
+            return Err(SubsystemError::PortIdentifierUnavailable);
+        };
+        port.caps = PortCapabilities { ciaps, aems };
+        Ok(())
+    }
+
     pub fn add_controller(&mut self, port: PortId) -> Result<ControllerId, SubsystemError> {
         debug_assert!(self.ctlrs.len() <= u16::MAX.into());
         let cid = ControllerId(self.ctlrs.len() as u16);
-        let c = Controller::new(cid, port);
+        let mut builder = pcie::PciDeviceFunctionConfigurationSpace::builder();
+        builder
+            .vid(self.info.pci_vid)
+            .did(self.info.pci_did)
+            .svid(self.info.pci_svid)
+            .sdid(self.info.pci_sdid);
+        if let Some(Port {
+            typ: PortType::Pcie(pprt),
+            ..
+        }) = self.ports.iter().find(|p| p.id == port)
+        {
+            builder.port(pprt);
+        }
+        let pcie = builder.build();
+        let c = GenericController::new(cid, port, pcie, self.fr);
         self.ctlrs
             .push(c)
             .map_err(|_| SubsystemError::ControllerLimitExceeded)?;
         Ok(cid)
     }
 
-    pub fn controller_mut(&mut self, id: ControllerId) -> &mut Controller {
+    pub fn controllers(&self) -> impl Iterator<Item = &GenericController<NSS>> {
+        self.ctlrs.iter()
+    }
+
+    pub fn controller_mut(&mut self, id: ControllerId) -> &mut GenericController<NSS> {
         self.ctlrs
             .get_mut(id.0 as usize)
             .expect("Invalid ControllerId provided")
     }
 
+    pub fn namespaces(&self) -> impl Iterator<Item = &Namespace> {
+        self.nss.iter()
+    }
+
+    // Base v2.1, 3.2.1.4: An allocated namespace attached to no controller is inactive
+    pub fn namespace_attached(&self, nsid: NamespaceId) -> bool {
+        self.ctlrs
+            .iter()
+            .flat_map(|c| c.active_ns.iter())
+            .any(|&id| id == nsid)
+    }
+
+    // Capacity in logical blocks, as for NCAP. The UUID is derived from the subsystem
+    // instance and the NSID
     pub fn add_namespace(&mut self, capacity: u64) -> Result<NamespaceId, SubsystemError> {
         let Some(allocated) = self.nsids.checked_add(1) else {
             debug!("Implement allocation tracking with reuse");
@@ -698,15 +2244,303 @@ impl Subsystem {
         }
     }
 
+    pub fn add_namespace_with_uuid(
+        &mut self,
+        capacity: u64,
+        uuid: Uuid,
+    ) -> Result<NamespaceId, SubsystemError> {
+        self.add_namespace_with(capacity, &[NamespaceIdentifierType::Nuuid(uuid)])
+    }
+
+    // Each identifier replaces any of the same type, including the derived UUID
+    pub fn add_namespace_with(
+        &mut self,
+        capacity: u64,
+        nids: &[NamespaceIdentifierType],
+    ) -> Result<NamespaceId, SubsystemError> {
+        let nsid = self.add_namespace(capacity)?;
+        let ns = self.namespace_mut(nsid);
+        for nid in nids {
+            ns.set_nid(*nid);
+        }
+        Ok(nsid)
+    }
+
+    pub fn namespace_uuid(&self, nsid: NamespaceId) -> Option<Uuid> {
+        self.nss
+            .iter()
+            .find(|ns| ns.id == nsid)
+            .and_then(Namespace::uuid)
+    }
+
+    pub fn namespace_mut(&mut self, id: NamespaceId) -> &mut Namespace {
+        self.nss
+            .iter_mut()
+            .find(|ns| ns.id == id)
+            .expect("Invalid NamespaceId provided")
+    }
+
+    // Logical blocks allocated, as reported by NUSE
+    pub fn set_namespace_used(
+        &mut self,
+        nsid: NamespaceId,
+        used: u64,
+    ) -> Result<(), SubsystemError> {
+        let Some(ns) = self.nss.iter_mut().find(|ns| ns.id == nsid) else {
+            return Err(SubsystemError::NamespaceIdentifierUnavailable);
+        };
+
+        // NVM Command Set v1.0c, 4.1.5.1, Figure 97: NUSE is bounded by NCAP
+        if used > ns.capacity {
+            debug!(
+                "NSID {} usage {used} exceeds capacity {}",
+                nsid.0, ns.capacity
+            );
+            return Err(SubsystemError::NamespaceUseExceedsCapacity);
+        }
+
+        ns.used = used;
+        Ok(())
+    }
+
     pub fn remove_namespace(&mut self, nsid: NamespaceId) -> Result<(), SubsystemError> {
         if nsid.0 == u32::MAX {
+            for ns in &self.nss {
+                Self::detach_deleted_namespace(&mut self.ctlrs, ns.id);
+            }
             self.nss.clear();
             return Ok(());
         }
         let Some(e) = self.nss.iter().enumerate().find(|args| args.1.id == nsid) else {
             return Err(SubsystemError::NamespaceIdentifierUnavailable);
         };
+        Self::detach_deleted_namespace(&mut self.ctlrs, nsid);
         let _ = self.nss.swap_remove(e.0);
         Ok(())
     }
+
+    // Base v2.1, 8.1.15.4: Deletion detaches the namespace from all controllers
+    fn detach_deleted_namespace(
+        ctlrs: &mut heapless::Vec<GenericController<NSS>, CTLRS>,
+        nsid: NamespaceId,
+    ) {
+        for ctlr in ctlrs.iter_mut() {
+            if ctlr.active_ns.contains(&nsid) {
+                let _ = ctlr.detach_namespace(nsid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        ChangedNamespaceList, MAX_CHANGED_NAMESPACES, MessageIntegrityCheck, NamespaceId, PciePort,
+        PortId, PortType, Subsystem, SubsystemError, SubsystemInfo, SubsystemInfoError,
+    };
+
+    #[test]
+    fn ieee_oui_wire() {
+        let info = SubsystemInfo::invalid();
+        assert_eq!(info.ieee_oui, [0xac, 0xde, 0x48]);
+        assert_eq!(info.ieee_oui_wire(), [0x48, 0xde, 0xac]);
+    }
+
+    #[test]
+    fn parse_ieee_oui() {
+        assert_eq!(
+            SubsystemInfo::parse_ieee_oui("ac-de-48"),
+            Some([0xac, 0xde, 0x48])
+        );
+        assert_eq!(
+            SubsystemInfo::parse_ieee_oui("00-0C-CA"),
+            Some([0x00, 0x0c, 0xca])
+        );
+    }
+
+    #[test]
+    fn parse_ieee_oui_malformed() {
+        for oui in [
+            "",
+            "ac",
+            "ac-de",
+            "ac-de-48-00",
+            "ac-de-4",
+            "ac-de-480",
+            "ac:de:48",
+            "ac-de-zz",
+            "ac-de-+4",
+            "acde48",
+        ] {
+            assert_eq!(SubsystemInfo::parse_ieee_oui(oui), None, "{oui}");
+        }
+    }
+
+    #[test]
+    fn namespace_uuid_stable() {
+        let generate = || {
+            let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+            let nsid = subsys.add_namespace(1024).unwrap();
+            subsys.namespace_uuid(nsid).unwrap()
+        };
+        let uuid = generate();
+        assert_eq!(uuid, generate());
+        assert_eq!(
+            uuid,
+            Uuid::from_u128(0xf7ce0b65_3d2d_42a4_908c_f5abe912ffdd)
+        );
+        assert_eq!(uuid.get_version(), Some(uuid::Version::Random));
+    }
+
+    #[test]
+    fn namespace_uuid_instance() {
+        let mut a = Subsystem::new(SubsystemInfo::invalid());
+        let mut b = Subsystem::new(SubsystemInfo::with_ids(
+            0xffff,
+            0xffff,
+            0xffff,
+            0xffff,
+            [0xac, 0xde, 0x48],
+            [1u8; 16],
+        ));
+        let nsa = a.add_namespace(1024).unwrap();
+        let nsb = b.add_namespace(1024).unwrap();
+        assert_eq!(nsa, nsb);
+        assert_ne!(a.namespace_uuid(nsa), b.namespace_uuid(nsb));
+    }
+
+    #[test]
+    fn namespace_uuid_explicit() {
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+        let nsid = subsys.add_namespace_with_uuid(1024, uuid).unwrap();
+        assert_eq!(subsys.namespace_uuid(nsid), Some(uuid));
+        assert_eq!(subsys.namespace_uuid(NamespaceId(nsid.0 + 1)), None);
+    }
+
+    #[test]
+    fn controller_transfer_permitted() {
+        let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+        let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+        let cid = subsys.add_controller(ppid).unwrap();
+        let ctlr = subsys.controller_mut(cid);
+        assert!(ctlr.transfer_permitted(u32::MAX));
+        ctlr.set_max_data_transfer_size(1);
+        assert!(ctlr.transfer_permitted(8192));
+        assert!(!ctlr.transfer_permitted(8196));
+        ctlr.set_max_data_transfer_size(u8::MAX);
+        assert!(ctlr.transfer_permitted(u32::MAX));
+    }
+
+    #[test]
+    fn acquire_ieee_oui() {
+        assert_eq!(
+            SubsystemInfo::acquire_ieee_oui(None),
+            Ok([0xac, 0xde, 0x48])
+        );
+        assert_eq!(
+            SubsystemInfo::acquire_ieee_oui(Some("00-0c-ca")),
+            Ok([0x00, 0x0c, 0xca])
+        );
+        assert_eq!(
+            SubsystemInfo::acquire_ieee_oui(Some("00-0c")),
+            Err(SubsystemInfoError::IeeeOuiInvalid)
+        );
+        assert_eq!(
+            SubsystemInfo::acquire_ieee_oui(Some("00-0c-xy")),
+            Err(SubsystemInfoError::IeeeOuiInvalid)
+        );
+    }
+
+    #[test]
+    fn parse_pci_id() {
+        assert_eq!(SubsystemInfo::parse_pci_id("1b96"), Some(0x1b96));
+        assert_eq!(SubsystemInfo::parse_pci_id("FFFF"), Some(0xffff));
+        assert_eq!(SubsystemInfo::parse_pci_id("1"), Some(0x0001));
+    }
+
+    #[test]
+    fn parse_pci_id_malformed() {
+        for id in ["", "10000", "1b9g", "+1b9", "0x1b"] {
+            assert_eq!(SubsystemInfo::parse_pci_id(id), None, "{id}");
+        }
+    }
+
+    #[test]
+    fn acquire_pci_id() {
+        const ERR: SubsystemInfoError = SubsystemInfoError::PciVidInvalid;
+        assert_eq!(SubsystemInfo::acquire_pci_id(None, ERR), Ok(0xffff));
+        assert_eq!(SubsystemInfo::acquire_pci_id(Some("1b96"), ERR), Ok(0x1b96));
+        assert_eq!(SubsystemInfo::acquire_pci_id(Some("zzzz"), ERR), Err(ERR));
+        assert_eq!(SubsystemInfo::acquire_pci_id(Some("1b960"), ERR), Err(ERR));
+        assert_eq!(SubsystemInfo::acquire_pci_id(Some(""), ERR), Err(ERR));
+    }
+
+    #[test]
+    fn acquire_source_date_epoch() {
+        assert_eq!(
+            SubsystemInfo::acquire_source_date_epoch("1700000000"),
+            Ok(1700000000)
+        );
+        assert_eq!(
+            SubsystemInfo::acquire_source_date_epoch("yesterday"),
+            Err(SubsystemInfoError::SourceDateEpochInvalid)
+        );
+    }
+
+    #[test]
+    fn environment() {
+        assert!(SubsystemInfo::environment().is_ok());
+    }
+
+    #[test]
+    fn message_integrity_check_default() {
+        // MI v2.0, 3.1: IC set, NVMe-MI message type
+        assert_eq!(
+            MessageIntegrityCheck::new(crc::CRC_32_ISCSI.init, 0x80 | 0x04),
+            MessageIntegrityCheck::default()
+        );
+        assert_ne!(
+            MessageIntegrityCheck::new(crc::CRC_32_ISCSI.init, 0x04),
+            MessageIntegrityCheck::default()
+        );
+    }
+
+    #[test]
+    fn port_mmtus() {
+        let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+        let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+
+        assert_eq!(
+            subsys.set_port_mmtus(ppid, 63),
+            Err(SubsystemError::PortMtuInvalid)
+        );
+        assert_eq!(
+            subsys.set_port_mmtus(PortId::new(1), 128),
+            Err(SubsystemError::PortIdentifierUnavailable)
+        );
+
+        subsys.set_port_mmtus(ppid, 256).unwrap();
+        subsys.port_mut(ppid).unwrap().mtus = 256;
+        subsys.set_port_mmtus(ppid, 128).unwrap();
+        assert_eq!(subsys.port_mut(ppid).unwrap().mmtus, 128);
+        assert_eq!(subsys.port_mut(ppid).unwrap().mtus, 128);
+    }
+
+    #[test]
+    fn changed_namespace_list_overflow() {
+        let mut chns = ChangedNamespaceList::new();
+        for nsid in 1..=MAX_CHANGED_NAMESPACES as u32 {
+            chns.record(NamespaceId(nsid));
+        }
+        assert!(!chns.overflow);
+        assert_eq!(chns.nsids.len(), MAX_CHANGED_NAMESPACES);
+
+        // Base v2.1, 5.1.12.1.5: Beyond the list capacity only the FFFFFFFFh entry is reported
+        chns.record(NamespaceId(MAX_CHANGED_NAMESPACES as u32 + 1));
+        assert!(chns.overflow);
+        assert!(chns.nsids.is_empty());
+    }
 }
@@ -21,10 +21,20 @@ pub enum ControllerProperties {
     Cc(ControllerConfiguration) = 0x14,
 }
 
+// Base v2.1, 3.1.4.5, Figure 41, SHN
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ShutdownNotification {
+    #[default]
+    None,
+    Normal,
+    Abrupt,
+}
+
 // Base v2.1, 3.1.4.5, Figure 41
 #[derive(Clone, Copy, Debug, Default)]
 pub struct ControllerConfiguration {
     pub en: bool,
+    pub shn: ShutdownNotification,
 }
 
 // Base v2.1, 3.1.4.6, Figure 42
@@ -62,10 +72,10 @@ impl From<AdminIoCqeStatus> for u32 {
         debug_assert_eq!((sct & !7), 0);
         let sc: u32 = match value.status {
             AdminIoCqeStatusType::GenericCommandStatus(s) => s.id(),
-            AdminIoCqeStatusType::CommandSpecificStatus(v) => v,
-            AdminIoCqeStatusType::MediaAndDataIntegrityErrors => todo!(),
-            AdminIoCqeStatusType::PathRelatedStatus => todo!(),
-            AdminIoCqeStatusType::VendorSpecific => todo!(),
+            AdminIoCqeStatusType::CommandSpecificStatus(v)
+            | AdminIoCqeStatusType::MediaAndDataIntegrityErrors(v)
+            | AdminIoCqeStatusType::PathRelatedStatus(v)
+            | AdminIoCqeStatusType::VendorSpecific(v) => v,
         }
         .into();
         debug_assert_eq!((sc & !0xff), 0);
@@ -93,11 +103,11 @@ enum AdminIoCqeStatusType {
     GenericCommandStatus(AdminIoCqeGenericCommandStatus) = 0x00,
     CommandSpecificStatus(u8) = 0x01,
     #[expect(dead_code)]
-    MediaAndDataIntegrityErrors = 0x02,
+    MediaAndDataIntegrityErrors(u8) = 0x02,
     #[expect(dead_code)]
-    PathRelatedStatus = 0x03,
+    PathRelatedStatus(u8) = 0x03,
     #[expect(dead_code)]
-    VendorSpecific = 0x07,
+    VendorSpecific(u8) = 0x07,
 }
 unsafe impl Discriminant<u8> for AdminIoCqeStatusType {}
 
@@ -109,6 +119,9 @@ enum AdminIoCqeGenericCommandStatus {
     InvalidFieldInCommand = 0x02,
     InternalError = 0x06,
     InvalidNamespaceOrFormat = 0x0b,
+    CommandSequenceError = 0x0c,
+    SanitizeInProgress = 0x1d,
+    CommandInterrupted = 0x21,
 }
 unsafe impl Discriminant<u8> for AdminIoCqeGenericCommandStatus {}
 
@@ -177,6 +190,72 @@ impl TryFrom<u32> for AdminFormatNvmConfiguration {
     }
 }
 
+// Base v2.1, Virtualization Management, CDW10, ACT
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum VirtualizationManagementAction {
+    PrimaryFlexibleAllocation = 0x1,
+    SecondaryOffline = 0x7,
+    SecondaryAssign = 0x8,
+    SecondaryOnline = 0x9,
+}
+unsafe impl Discriminant<u8> for VirtualizationManagementAction {}
+
+impl TryFrom<u32> for VirtualizationManagementAction {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0x1 => Ok(Self::PrimaryFlexibleAllocation),
+            0x7 => Ok(Self::SecondaryOffline),
+            0x8 => Ok(Self::SecondaryAssign),
+            0x9 => Ok(Self::SecondaryOnline),
+            _ => Err(()),
+        }
+    }
+}
+
+// Base v2.1, Virtualization Management, CDW10, RT
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ControllerResourceType {
+    Vq = 0x0,
+    Vi = 0x1,
+}
+unsafe impl Discriminant<u8> for ControllerResourceType {}
+
+impl TryFrom<u32> for ControllerResourceType {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0x0 => Ok(Self::Vq),
+            0x1 => Ok(Self::Vi),
+            _ => Err(()),
+        }
+    }
+}
+
+// Base v2.1, Virtualization Management, CDW10
+#[derive(Debug)]
+struct AdminVirtualizationManagementConfiguration {
+    act: VirtualizationManagementAction,
+    rt: u32,
+    cntlid: u16,
+}
+
+impl TryFrom<u32> for AdminVirtualizationManagementConfiguration {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(Self {
+            act: TryFrom::try_from(value & 0xf)?,
+            rt: (value >> 8) & 0x7,
+            cntlid: (value >> 16) as u16,
+        })
+    }
+}
+
 // Base v2.1, 5.1.12, Figure 202
 // MI v2.0, 6.3, Figure 141
 #[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
@@ -186,6 +265,10 @@ pub enum AdminGetLogPageLidRequestType {
     SupportedLogPages = 0x00,
     ErrorInformation = 0x01,
     SmartHealthInformation = 0x02,
+    FirmwareSlotInformation = 0x03,
+    ChangedNamespaceList = 0x04,
+    CommandsSupportedAndEffects = 0x05,
+    DeviceSelfTest = 0x06,
     FeatureIdentifiersSupportedAndEffects = 0x12,
     SanitizeStatus = 0x81,
 }
@@ -267,6 +350,125 @@ pub struct SmartHealthInformationLogPageResponse {
 }
 impl Encode<512> for SmartHealthInformationLogPageResponse {}
 
+// Base v2.1, 5.1.12.1.4, Figure 207
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct FirmwareSlotInformationLogPageResponse {
+    #[deku(pad_bytes_after = "7")]
+    afi: u8,
+    #[deku(pad_bytes_after = "448")]
+    frs: [WireString<8>; 7],
+}
+impl Encode<512> for FirmwareSlotInformationLogPageResponse {}
+
+// Base v2.1, 5.1.12.1.5, Figure 208
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct ChangedNamespaceListLogPageResponse {
+    nsid: WireVec<u32, { crate::MAX_CHANGED_NAMESPACES }>,
+}
+impl Encode<4096> for ChangedNamespaceListLogPageResponse {}
+
+impl ChangedNamespaceListLogPageResponse {
+    fn new() -> Self {
+        Self {
+            nsid: WireVec::new(),
+        }
+    }
+}
+
+// Base v2.1, 5.1.12.1.6, Figure 210, DSTS, STC
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum DeviceSelfTestCode {
+    Short = 0x1,
+    Extended = 0x2,
+    VendorSpecific = 0xe,
+}
+unsafe impl crate::Discriminant<u8> for DeviceSelfTestCode {}
+
+// Base v2.1, 5.1.12.1.6, Figure 210, DSTS, Result
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum DeviceSelfTestResult {
+    Completed = 0x0,
+    AbortedByCommand = 0x1,
+    AbortedByReset = 0x2,
+    AbortedByNamespaceRemoval = 0x3,
+    AbortedByFormat = 0x4,
+    FatalError = 0x5,
+    UnknownSegmentFailed = 0x6,
+    SegmentFailed = 0x7,
+    AbortedUnknown = 0x8,
+    AbortedBySanitize = 0x9,
+}
+unsafe impl crate::Discriminant<u8> for DeviceSelfTestResult {}
+
+// Base v2.1, 5.1.12.1.6, Figure 210
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct DeviceSelfTestResultDataStructure {
+    dsts: u8,
+    sn: u8,
+    #[deku(pad_bytes_after = "1")]
+    vdi: u8,
+    poh: u64,
+    nsid: u32,
+    flba: u64,
+    sct: u8,
+    sc: u8,
+    vs: u16,
+}
+
+impl Default for DeviceSelfTestResultDataStructure {
+    fn default() -> Self {
+        Self {
+            // Base v2.1, 5.1.12.1.6, Figure 210, DSTS: Entry not used
+            dsts: 0xf,
+            sn: 0,
+            vdi: 0,
+            poh: 0,
+            nsid: 0,
+            flba: 0,
+            sct: 0,
+            sc: 0,
+            vs: 0,
+        }
+    }
+}
+
+// Base v2.1, 5.1.12.1.6, Figure 209
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct DeviceSelfTestLogPageResponse {
+    cdsto: u8,
+    #[deku(pad_bytes_after = "2")]
+    cdstc: u8,
+    dstrd: [DeviceSelfTestResultDataStructure; 20],
+}
+impl Encode<564> for DeviceSelfTestLogPageResponse {}
+
+// Base v2.1, 5.1.12.1, Commands Supported and Effects Data Structure
+flags! {
+    pub enum CommandSupportedAndEffectsFlags: u32 {
+        Csupp = 1 << 0,
+        Lbcc = 1 << 1,
+        Ncc = 1 << 2,
+        Nic = 1 << 3,
+        Ccc = 1 << 4,
+    }
+}
+
+// Base v2.1, 5.1.12.1, Commands Supported and Effects
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct CommandsSupportedAndEffectsLogPageResponse {
+    // No I/O commands are supported, leaving IOCS and the reserved region zeroed
+    #[deku(pad_bytes_after = "3072")]
+    acs: [WireFlagSet<CommandSupportedAndEffectsFlags>; 256],
+}
+impl Encode<4096> for CommandsSupportedAndEffectsLogPageResponse {}
+
 // Base v2.1, 5.1.12.1.18, Figure 262
 flags! {
     pub enum FidSupportedAndEffectsFlags: u32 {
@@ -315,6 +517,27 @@ pub struct SanitizeStatus {
     mvcncled: bool,
 }
 
+impl SanitizeStatus {
+    pub(crate) fn in_progress(&self) -> bool {
+        self.sos == SanitizeOperationStatus::Sanitizing
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.sos = SanitizeOperationStatus::Sanitizing;
+        self.gde = false;
+        self.mvcncled = false;
+    }
+
+    pub(crate) fn complete(&mut self, success: bool) {
+        if success {
+            self.sos = SanitizeOperationStatus::Sanitized;
+            self.gde = true;
+        } else {
+            self.sos = SanitizeOperationStatus::SanitizeFailed;
+        }
+    }
+}
+
 impl From<SanitizeStatus> for u16 {
     fn from(value: SanitizeStatus) -> Self {
         ((value.mvcncled as u16) << 9)
@@ -347,6 +570,26 @@ pub struct SanitizeStateInformation {
     fails: u8,
 }
 
+impl SanitizeStateInformation {
+    pub(crate) fn processing() -> Self {
+        Self {
+            sans: SanitizeState::UnrestrictedProcessing,
+            fails: 0,
+        }
+    }
+
+    pub(crate) fn completed(success: bool) -> Self {
+        Self {
+            sans: if success {
+                SanitizeState::Idle
+            } else {
+                SanitizeState::UnrestrictedFailure
+            },
+            fails: 0,
+        }
+    }
+}
+
 impl From<SanitizeStateInformation> for u8 {
     fn from(value: SanitizeStateInformation) -> Self {
         (value.fails << 4) | (value.sans.id())
@@ -388,6 +631,7 @@ enum AdminIdentifyCnsRequestType {
     IdentifyNamespaceForAllocatedNamespaceId = 0x11,
     NamespaceAttachedControllerList = 0x12,
     NvmSubsystemControllerList = 0x13,
+    PrimaryControllerCapabilities = 0x14,
     SecondaryControllerList = 0x15,
 }
 unsafe impl Discriminant<u8> for AdminIdentifyCnsRequestType {}
@@ -408,30 +652,123 @@ pub struct AdminIdentifyNvmIdentifyNamespaceResponse {
     dps: u8,
     #[deku(seek_from_current = "18")]
     nvmcap: u128,
-    #[deku(seek_from_current = "64")]
-    // FIXME: use another struct
-    lbaf0: u16,
-    lbaf0_lbads: u8,
-    lbaf0_rp: u8,
+    #[deku(seek_from_current = "40")]
+    nguid: [u8; 16],
+    eui64: [u8; 8],
+    lbaf: [LbaFormat; LBA_FORMATS],
 }
 impl Encode<4096> for AdminIdentifyNvmIdentifyNamespaceResponse {}
 
+impl AdminIdentifyNvmIdentifyNamespaceResponse {
+    // Base v2.1, 5.1.13.2.1: Capabilities common to all namespaces for the
+    // broadcast NSID
+    fn common() -> Self {
+        Self {
+            nlbaf: (LBA_FORMATS - 1) as u8,
+            lbaf: LbaFormat::supported(),
+            ..Default::default()
+        }
+    }
+}
+
+const LBA_FORMATS: usize = (crate::MAX_BLOCK_ORDER - crate::MIN_BLOCK_ORDER + 1) as usize;
+
+// NVM Command Set v1.0c, 4.1.5.1, Figure 98
+#[derive(Clone, Copy, Debug, Default, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct LbaFormat {
+    ms: u16,
+    lbads: u8,
+    rp: u8,
+}
+
+impl LbaFormat {
+    // Format index i has an LBA data size of 2^(MIN_BLOCK_ORDER + i)
+    fn supported() -> [Self; LBA_FORMATS] {
+        core::array::from_fn(|i| Self {
+            ms: 0,
+            lbads: crate::MIN_BLOCK_ORDER + i as u8,
+            rp: 0,
+        })
+    }
+}
+
 impl From<&crate::Namespace> for AdminIdentifyNvmIdentifyNamespaceResponse {
     fn from(value: &crate::Namespace) -> Self {
         Self {
             nsze: value.size,
             ncap: value.capacity,
             nuse: value.used,
-            nsfeat: ((value.size == value.capacity) as u8),
-            nlbaf: 0,
-            flbas: 0,
+            // NVM Command Set v1.0c, 4.1.5.1, Figure 97, THINP: A namespace
+            // that is not fully allocated is thinly provisioned
+            nsfeat: (value.used < value.size) as u8,
+            nlbaf: (LBA_FORMATS - 1) as u8,
+            flbas: value.block_order - crate::MIN_BLOCK_ORDER,
             mc: 0,
             dpc: 0,
             dps: 0,
-            nvmcap: 2_u128.pow(value.block_order as u32) * value.size as u128,
-            lbaf0: 0,
-            lbaf0_lbads: value.block_order,
-            lbaf0_rp: 0,
+            // NVM Command Set v1.0c, 4.1.5.1, Figure 97: NVMCAP is in bytes and
+            // reflects the allocated capacity rather than the namespace size
+            nvmcap: (value.capacity as u128) << value.block_order,
+            nguid: value
+                .nids
+                .iter()
+                .find_map(|nid| match nid {
+                    crate::NamespaceIdentifierType::Nguid(v) => Some(*v),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+            eui64: value
+                .nids
+                .iter()
+                .find_map(|nid| match nid {
+                    crate::NamespaceIdentifierType::Ieuid(v) => Some(*v),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+            lbaf: LbaFormat::supported(),
+        }
+    }
+}
+
+// Base v2.1, 5.1.13.2.8
+#[derive(Debug, Default, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+pub struct AdminIdentifyIoCommandSetIndependentNamespaceResponse {
+    nsfeat: u8,
+    nmic: u8,
+    rescap: u8,
+    fpi: u8,
+    anagrpid: u32,
+    nsattr: u8,
+    #[deku(seek_from_current = "1")]
+    nvmsetid: u16,
+    endgid: u16,
+    nstat: u8,
+}
+impl Encode<4096> for AdminIdentifyIoCommandSetIndependentNamespaceResponse {}
+
+impl AdminIdentifyIoCommandSetIndependentNamespaceResponse {
+    // Base v2.1, 5.1.13.2.8: For the broadcast NSID, report the capabilities
+    // provided by any namespace. Per-namespace state is not merged.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            nsfeat: self.nsfeat | other.nsfeat,
+            nmic: self.nmic | other.nmic,
+            rescap: self.rescap | other.rescap,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&crate::Namespace> for AdminIdentifyIoCommandSetIndependentNamespaceResponse {
+    fn from(value: &crate::Namespace) -> Self {
+        Self {
+            // SHRNS: Only a private namespace is restricted to one controller
+            nmic: (!value.private) as u8,
+            // NRDY: Allocated namespaces are immediately ready
+            nstat: 1,
+            ..Default::default()
         }
     }
 }
@@ -448,6 +785,44 @@ pub enum CommandSetIdentifier {
     ComputationalPrograms = 0x04,
 }
 
+impl TryFrom<u8> for CommandSetIdentifier {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0x00 => Ok(Self::Nvm),
+            0x01 => Ok(Self::KeyValue),
+            0x02 => Ok(Self::ZonedNamespace),
+            0x03 => Ok(Self::SubsystemLocalMemory),
+            0x04 => Ok(Self::ComputationalPrograms),
+            _ => Err(value),
+        }
+    }
+}
+
+// Base v2.1, Identify I/O Command Set data structure, I/O Command Set Vector
+flags! {
+    pub enum IoCommandSetFlags: u8 {
+        Nvm = 1 << 0,
+        KeyValue = 1 << 1,
+        ZonedNamespace = 1 << 2,
+        SubsystemLocalMemory = 1 << 3,
+        ComputationalPrograms = 1 << 4,
+    }
+}
+
+impl From<CommandSetIdentifier> for IoCommandSetFlags {
+    fn from(value: CommandSetIdentifier) -> Self {
+        match value {
+            CommandSetIdentifier::Nvm => Self::Nvm,
+            CommandSetIdentifier::KeyValue => Self::KeyValue,
+            CommandSetIdentifier::ZonedNamespace => Self::ZonedNamespace,
+            CommandSetIdentifier::SubsystemLocalMemory => Self::SubsystemLocalMemory,
+            CommandSetIdentifier::ComputationalPrograms => Self::ComputationalPrograms,
+        }
+    }
+}
+
 // Base v2.1, 5.1.13.2.1, Figure 312, CNTRLTYPE
 #[derive(Clone, Copy, Debug, DekuRead, DekuWrite, PartialEq)]
 #[deku(id_type = "u8", endian = "endian", ctx = "endian: Endian")]
@@ -469,6 +844,24 @@ impl From<crate::ControllerType> for ControllerType {
     }
 }
 
+// Base v2.1, 5.1.13.2.1, Figure 312, OACS
+flags! {
+    #[repr(u16)]
+    pub enum OptionalAdminCommandSupport: u16 {
+        Ssrs,
+        Fnvms,
+        Fwds,
+        Nms,
+        Dsts,
+        Dirs,
+        Nsmirs,
+        Vms,
+        Dbcs,
+        Glss,
+        Clss,
+    }
+}
+
 // Base v2.1, 5.1.13.2.1, Figure 312, LPA
 flags! {
     #[repr(u8)]
@@ -565,7 +958,7 @@ struct AdminIdentifyControllerResponse {
     nvmsr: u8,
     vwci: u8,
     mec: u8,
-    ocas: u16,
+    oacs: WireFlagSet<OptionalAdminCommandSupport>,
     acl: u8,
     aerl: u8,
     frmw: u8,
@@ -687,6 +1080,72 @@ impl ControllerListResponse {
     }
 }
 
+// Base v2.1, 5.1.13.2.13, Figure 315
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct AdminIdentifyPrimaryControllerCapabilitiesResponse {
+    cntlid: u16,
+    portid: u16,
+    crt: WireFlagSet<ControllerResourceTypes>,
+    #[deku(seek_from_current = "27")]
+    vqfrt: u32,
+    vqrfa: u32,
+    vqrfap: u16,
+    vqprt: u16,
+    vqfrsm: u16,
+    vqgran: u16,
+    #[deku(seek_from_current = "16")]
+    vifrt: u32,
+    virfa: u32,
+    virfap: u16,
+    viprt: u16,
+    vifrsm: u16,
+    vigran: u16,
+}
+impl Encode<4096> for AdminIdentifyPrimaryControllerCapabilitiesResponse {}
+
+// Base v2.1, 5.1.13.2.13, Figure 315, CRT
+flags! {
+    enum ControllerResourceTypes: u8 {
+        Vq = 1 << 0,
+        Vi = 1 << 1,
+    }
+}
+
+// Base v2.1, 5.1.13.2.14, Figure 316
+#[derive(Debug, DekuWrite)]
+#[deku(endian = "little")]
+struct SecondaryControllerListResponse {
+    #[deku(update = "self.entries.len()", pad_bytes_after = "31")]
+    numid: u8,
+    #[deku(count = "numid")]
+    entries: WireVec<SecondaryControllerEntry, { crate::MAX_SECONDARY_CONTROLLERS }>,
+}
+impl Encode<4096> for SecondaryControllerListResponse {}
+
+impl SecondaryControllerListResponse {
+    fn new() -> Self {
+        Self {
+            numid: 0,
+            entries: WireVec::new(),
+        }
+    }
+}
+
+// Base v2.1, 5.1.13.2.14, Figure 317
+#[derive(Debug, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct SecondaryControllerEntry {
+    scid: u16,
+    pcid: u16,
+    #[deku(pad_bytes_after = "3")]
+    scs: u8,
+    vfn: u16,
+    nvq: u16,
+    #[deku(pad_bytes_after = "18")]
+    nvi: u16,
+}
+
 // Base v2.1, 5.1.20.1, Figure 364, SEL
 #[derive(Debug, DekuRead, Eq, PartialEq)]
 #[deku(ctx = "endian: Endian", endian = "endian", id_type = "u8")]
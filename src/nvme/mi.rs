@@ -7,7 +7,7 @@ use crate::nvme::{
     AdminNamespaceAttachmentSelect, AdminNamespaceManagementSelect, ControllerListRequest,
 };
 use crate::wire::{WireFlagSet, WireVec};
-use crate::{CommandEffectError, Discriminant, Encode, MAX_CONTROLLERS};
+use crate::{CommandEffectError, Discriminant, Encode};
 
 use super::{AdminGetLogPageLidRequestType, AdminIdentifyCnsRequestType};
 
@@ -15,6 +15,8 @@ use super::{AdminGetLogPageLidRequestType, AdminIdentifyCnsRequestType};
 /*
  * Copyright (c) 2025 Code Construct
  */
+#[cfg(feature = "decode")]
+pub mod decode;
 pub mod dev;
 
 // MI v2.0, 3.1.1, Figure 20, NMIMT
@@ -54,9 +56,9 @@ struct MessageHeader {
 impl Encode<3> for MessageHeader {}
 
 impl MessageHeader {
-    fn respond(nmimt: MessageType) -> Self {
+    fn respond(nmimt: MessageType, csi: bool) -> Self {
         Self {
-            nmimt: ((true as u8) << 7) | ((nmimt.id() & 0xf) << 3),
+            nmimt: ((true as u8) << 7) | ((nmimt.id() & 0xf) << 3) | (csi as u8),
         }
     }
 
@@ -78,16 +80,98 @@ impl MessageHeader {
 #[deku(endian = "endian", ctx = "endian: Endian", id_type = "u8")]
 #[repr(u8)]
 pub enum ResponseStatus {
+    #[deku(id = "0x00")]
     Success = 0x00,
+    #[deku(id = "0x01")]
+    MoreProcessingRequired = 0x01,
+    #[deku(id = "0x02")]
     InternalError = 0x02,
+    #[deku(id = "0x03")]
     InvalidCommandOpcode = 0x03,
-    InvalidParameter = 0x04,
+    #[deku(id = "0x04")]
+    InvalidParameter(ParameterErrorLocation) = 0x04,
+    #[deku(id = "0x05")]
     InvalidCommandSize = 0x05,
+    #[deku(id = "0x06")]
     InvalidCommandInputDataSize = 0x06,
+    #[deku(id = "0x07")]
     AccessDenied = 0x07,
+    #[deku(id = "0x20")]
+    VpdUpdatesExceeded = 0x20,
+    #[deku(id = "0x21")]
+    PcieInaccessible = 0x21,
+    #[deku(id = "0x22")]
+    ManagementEndpointBufferClearedDueToSanitize = 0x22,
+    #[deku(id = "0x23")]
+    EnclosureServicesFailure = 0x23,
+    #[deku(id = "0x24")]
+    EnclosureServicesTransferFailure = 0x24,
+    #[deku(id = "0x25")]
+    EnclosureFailure = 0x25,
+    #[deku(id = "0x26")]
+    EnclosureServicesTransferRefused = 0x26,
+    #[deku(id = "0x27")]
+    UnsupportedEnclosureFunction = 0x27,
+    #[deku(id = "0x28")]
+    EnclosureServicesNotAvailable = 0x28,
+    #[deku(id = "0x29")]
+    EnclosureDegraded = 0x29,
+    #[deku(id = "0x2a")]
+    SanitizeInProgress = 0x2a,
 }
 unsafe impl Discriminant<u8> for ResponseStatus {}
 
+// MI v2.0, 4.1.2, Figure 30
+#[derive(Clone, Copy, Debug, DekuRead, DekuWrite, Eq, PartialEq)]
+#[deku(endian = "endian", ctx = "endian: Endian")]
+pub struct ParameterErrorLocation {
+    bit: u8,
+    byte: u16,
+}
+
+impl ParameterErrorLocation {
+    // Offsets are relative to the start of the NVMe-MI message, which
+    // includes the MCTP message type byte
+    pub const fn new(byte: u16, bit: u8) -> Self {
+        debug_assert!(bit < 8);
+        Self { bit, byte }
+    }
+}
+
+impl core::fmt::Display for ResponseStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Success => f.write_str("Success"),
+            Self::MoreProcessingRequired => f.write_str("More Processing Required"),
+            Self::InternalError => f.write_str("Internal Error"),
+            Self::InvalidCommandOpcode => f.write_str("Invalid Command Opcode"),
+            Self::InvalidParameter(pel) => {
+                write!(f, "Invalid Parameter (byte {}, bit {})", pel.byte, pel.bit)
+            }
+            Self::InvalidCommandSize => f.write_str("Invalid Command Size"),
+            Self::InvalidCommandInputDataSize => f.write_str("Invalid Command Input Data Size"),
+            Self::AccessDenied => f.write_str("Access Denied"),
+            Self::VpdUpdatesExceeded => f.write_str("VPD Updates Exceeded"),
+            Self::PcieInaccessible => f.write_str("PCIe Inaccessible"),
+            Self::ManagementEndpointBufferClearedDueToSanitize => {
+                f.write_str("Management Endpoint Buffer Cleared Due to Sanitize")
+            }
+            Self::EnclosureServicesFailure => f.write_str("Enclosure Services Failure"),
+            Self::EnclosureServicesTransferFailure => {
+                f.write_str("Enclosure Services Transfer Failure")
+            }
+            Self::EnclosureFailure => f.write_str("Enclosure Failure"),
+            Self::EnclosureServicesTransferRefused => {
+                f.write_str("Enclosure Services Transfer Refused")
+            }
+            Self::UnsupportedEnclosureFunction => f.write_str("Unsupported Enclosure Function"),
+            Self::EnclosureServicesNotAvailable => f.write_str("Enclosure Services Not Available"),
+            Self::EnclosureDegraded => f.write_str("Enclosure Degraded"),
+            Self::SanitizeInProgress => f.write_str("Sanitize In Progress"),
+        }
+    }
+}
+
 impl From<DekuError> for ResponseStatus {
     fn from(err: DekuError) -> Self {
         debug!("Codec operation failed: {err}");
@@ -174,7 +258,8 @@ enum NvmeMiConfigurationIdentifierRequestType {
     HealthStatusChange(HealthStatusChangeRequest),
     #[deku(id = "0x03")]
     MctpTransmissionUnitSize(MctpTransmissionUnitSizeRequest),
-    AsynchronousEvent = 0x04,
+    #[deku(id = "0x04")]
+    AsynchronousEvent(AsynchronousEventRequest),
 }
 
 // MI v2.0, 5.1.1, Figure 77
@@ -206,6 +291,28 @@ struct GetMctpTransmissionUnitSizeResponse {
 }
 impl Encode<4> for GetMctpTransmissionUnitSizeResponse {}
 
+// MI v2.0, 5.1.4, AE Supported List
+#[derive(Debug, DekuWrite)]
+#[deku(endian = "little")]
+struct GetAsynchronousEventResponse {
+    #[deku(pad_bytes_after = "3")]
+    status: ResponseStatus,
+    aeslver: u8,
+    aeslhl: u8,
+    aesltl: u16,
+    numaes: u8,
+    aesd: WireVec<AsynchronousEventSupportedDescriptor, 64>,
+}
+impl Encode<137> for GetAsynchronousEventResponse {}
+
+// MI v2.0, 5.1.4, AE Supported Descriptor
+#[derive(Debug, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AsynchronousEventSupportedDescriptor {
+    aesdl: u8,
+    aesi: u8,
+}
+
 // MI v2.0, 5.2, Figure 84
 #[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
 #[deku(ctx = "endian: Endian", endian = "endian")]
@@ -217,9 +324,8 @@ struct NvmeMiConfigurationSetRequest {
 #[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
 #[deku(ctx = "endian: Endian", endian = "endian")]
 struct SmbusI2cFrequencyRequest {
-    // XXX: This is inaccurate as SFREQ is specified as 4 bits, not 8
-    // TODO: Support deku/bits feature without deku/alloc
-    dw0_sfreq: crate::nvme::mi::SmbusFrequency,
+    // SFREQ occupies bits 3:0, bits 7:4 are reserved
+    dw0_sfreq: u8,
     // Skip intermediate bytes in DWORD 0
     #[deku(seek_from_current = "1")]
     dw0_portid: u8,
@@ -265,6 +371,37 @@ struct MctpTransmissionUnitSizeRequest {
     dw1_mtus: u16,
 }
 
+// MI v2.0, 5.1.4, 5.2.4
+#[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AsynchronousEventRequest {
+    dw0_rsvd: [u8; 3],
+    dw1_rsvd: [u8; 4],
+}
+
+impl AsynchronousEventRequest {
+    fn reserved(&self) -> Option<ParameterErrorLocation> {
+        if let Some(idx) = self.dw0_rsvd.iter().position(|b| *b != 0) {
+            return Some(ParameterErrorLocation::new(9 + idx as u16, 0));
+        }
+
+        self.dw1_rsvd
+            .iter()
+            .position(|b| *b != 0)
+            .map(|idx| ParameterErrorLocation::new(12 + idx as u16, 0))
+    }
+}
+
+// MI v2.0, 5.2.4, AE Enable List Header
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(endian = "little")]
+struct AsynchronousEventEnableListHeader {
+    aeelver: u8,
+    aeelhl: u8,
+    aeeltl: u16,
+    numaee: u8,
+}
+
 // MI v2.0, 5.3, Figure 94
 flags! {
     pub enum ControllerFunctionAndReportingFlags: u8 {
@@ -300,13 +437,14 @@ struct ControllerHealthStatusPollRequest {
 // MI v2.0, 5.3, Figure 96
 #[derive(Debug, DekuRead, DekuWrite)]
 #[deku(endian = "little")]
-struct ControllerHealthStatusPollResponse {
+struct ControllerHealthStatusPollResponse<const CTLRS: usize> {
     status: ResponseStatus,
     #[deku(pad_bytes_before = "2", update = "self.body.len() as u8")]
     rent: u8,
-    body: WireVec<ControllerHealthDataStructure, MAX_CONTROLLERS>,
+    body: WireVec<ControllerHealthDataStructure, CTLRS>,
 }
-impl Encode<{ 4 + 16 * MAX_CONTROLLERS }> for ControllerHealthStatusPollResponse {}
+// RENT is bounded by MAXRENT, so at most 255 entries of 16 bytes are encoded
+impl<const CTLRS: usize> Encode<4096> for ControllerHealthStatusPollResponse<CTLRS> {}
 
 // MI v2.0, 5.3, Figure 97, CSTS
 flags! {
@@ -404,6 +542,25 @@ struct NvmSubsystemHealthStatusPollRequest {
     dword1: u32,
 }
 
+impl NvmSubsystemHealthStatusPollRequest {
+    // MI v2.0, 5.6, Figure 106, CS
+    const CS: u32 = 1 << 31;
+
+    fn cs(&self) -> bool {
+        (self.dword1 & Self::CS) != 0
+    }
+
+    fn reserved(&self) -> Option<ParameterErrorLocation> {
+        [(8, self.dword0), (12, self.dword1 & !Self::CS)]
+            .into_iter()
+            .find(|(_, dw)| *dw != 0)
+            .map(|(base, dw)| {
+                let bit = dw.trailing_zeros();
+                ParameterErrorLocation::new(base + (bit / 8) as u16, (bit % 8) as u8)
+            })
+    }
+}
+
 // MI v2.0, 5.6, Figure 107
 flags! {
     #[repr(u16)]
@@ -484,7 +641,6 @@ pub struct NvmSubsystemStatus {
     sfm: bool,
     df: bool,
     rnr: bool,
-    rd: bool,
 }
 
 impl Default for NvmSubsystemStatus {
@@ -500,7 +656,6 @@ impl NvmSubsystemStatus {
             sfm: false,
             df: true,
             rnr: true,
-            rd: false,
         }
     }
 }
@@ -555,7 +710,7 @@ impl Encode<4> for NvmeMiDataStructureManagementResponse {}
 
 // MI v2.0, 5.7.1, Figure 112, NNSC
 // TODO: Convert to Flags/FlagSet
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct SubsystemCapabilities {
     sre: bool,
 }
@@ -564,6 +719,10 @@ impl SubsystemCapabilities {
     pub fn new() -> Self {
         Self { sre: false }
     }
+
+    pub fn set_sre(&mut self, sre: bool) {
+        self.sre = sre;
+    }
 }
 
 impl Default for SubsystemCapabilities {
@@ -573,7 +732,7 @@ impl Default for SubsystemCapabilities {
 }
 
 // MI v2.0, 5.7.1, Figure 112
-#[derive(Debug, DekuWrite)]
+#[derive(Debug, DekuRead, DekuWrite)]
 #[deku(endian = "little")]
 struct NvmSubsystemInformationResponse {
     nump: u8,
@@ -700,6 +859,20 @@ pub enum SmbusFrequency {
 }
 unsafe impl Discriminant<u8> for SmbusFrequency {}
 
+impl TryFrom<u8> for SmbusFrequency {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0x00 => Ok(Self::FreqNotSupported),
+            0x01 => Ok(Self::Freq100Khz),
+            0x02 => Ok(Self::Freq400Khz),
+            0x03 => Ok(Self::Freq1Mhz),
+            _ => Err(value),
+        }
+    }
+}
+
 // MI v2.0, 5.7.2, Figure 116
 #[derive(Debug, DekuWrite)]
 #[deku(endian = "little")]
@@ -728,6 +901,43 @@ struct ControllerInformationResponse {
 }
 impl Encode<32> for ControllerInformationResponse {}
 
+// MI v2.0, 5.7.5, Figure 119, CMDTYPE
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+enum OptionallySupportedCommandType {
+    NvmeAdminCommand = 0x01,
+    PcieCommand = 0x02,
+}
+unsafe impl Discriminant<u8> for OptionallySupportedCommandType {}
+
+// MI v2.0, 5.7.5, Figure 119
+#[derive(Debug, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct OptionallySupportedCommandDataStructure {
+    cmdtype: u8,
+    opc: u8,
+}
+
+// MI v2.0, 5.7.5, Figure 118
+#[derive(Debug, DekuWrite)]
+#[deku(endian = "little")]
+struct OptionallySupportedCommandListResponse {
+    #[deku(update = "self.cmds.len()")]
+    numcmd: u16,
+    #[deku(count = "numcmd")]
+    cmds: WireVec<OptionallySupportedCommandDataStructure, 16>,
+}
+impl Encode<34> for OptionallySupportedCommandListResponse {}
+
+impl OptionallySupportedCommandListResponse {
+    fn new() -> Self {
+        Self {
+            numcmd: 0,
+            cmds: WireVec::new(),
+        }
+    }
+}
+
 // MI v2.0, 6, Figure 134
 #[expect(clippy::large_enum_variant)] // FIXME
 #[derive(Debug, DekuRead, PartialEq, Eq)]
@@ -742,16 +952,24 @@ enum AdminCommandRequestType {
     CreateIoCompletionQueue = 0x05, // P
     #[deku(id = 0x06)]
     Identify(AdminIdentifyRequest), // M
-    Abort = 0x08,                   // P
-    GetFeatures = 0x0a,             // M
-    AsynchronousEventRequest = 0x0c, // P
+    #[deku(id = 0x08)]
+    Abort(AdminAbortRequest),
+    #[deku(id = 0x09)]
+    SetFeatures(AdminSetFeaturesRequest),
+    #[deku(id = 0x0a)]
+    GetFeatures(AdminGetFeaturesRequest), // M
+    #[deku(id = 0x0c)]
+    AsynchronousEventRequest(AdminAsynchronousEventRequest),
     #[deku(id = 0x0d)]
     NamespaceManagement(AdminNamespaceManagementRequest),
     #[deku(id = 0x15)]
     NamespaceAttachement(AdminNamespaceAttachmentRequest),
-    KeepAlive = 0x18,                      // P
-    DirectiveSend = 0x19,                  // P
-    DirectiveReceive = 0x1a,               // P
+    #[deku(id = 0x18)]
+    KeepAlive(AdminKeepAliveRequest),
+    DirectiveSend = 0x19,    // P
+    DirectiveReceive = 0x1a, // P
+    #[deku(id = 0x1c)]
+    VirtualizationManagement(AdminVirtualizationManagementRequest),
     NvmeMiSend = 0x1d,                     // P
     NvmeMiReceive = 0x1e,                  // P
     DiscoveryInformationManagement = 0x21, // P
@@ -787,6 +1005,45 @@ struct AdminCommandRequestHeader {
     op: AdminCommandRequestType,
 }
 
+// MI v2.0, 6, Figure 136
+// Base v2.1, 5.1.1
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminAbortRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    dlen: u32,
+    #[deku(seek_from_current = "8")]
+    sqid: u16,
+    #[deku(pad_bytes_after = "20")]
+    cid: u16,
+}
+
+// MI v2.0, 6, Figure 136
+// Base v2.1, 5.1.2
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminAsynchronousEventRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    #[deku(pad_bytes_after = "32")]
+    dlen: u32,
+}
+
+// MI v2.0, 6, Figure 136
+// Base v2.1, 5.1.14
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminKeepAliveRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    #[deku(pad_bytes_after = "32")]
+    dlen: u32,
+}
+
 // MI v2.0, 6, Figure 136
 // Base v2.1, 5.1.10, Figure 189
 #[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
@@ -801,6 +1058,21 @@ struct AdminFormatNvmRequest {
     config: u32,
 }
 
+// MI v2.0, 6, Figure 136
+// Base v2.1, 5.1.11
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminGetFeaturesRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    dlen: u32,
+    #[deku(seek_from_current = "8")]
+    config: u32,
+    #[deku(pad_bytes_after = "16")]
+    value: u32,
+}
+
 // MI v2.0, 6, Figure 136
 // Base v2.1, 5.1.12, Figures 197-201
 #[derive(Debug, DekuRead, DekuWrite, Eq, PartialEq)]
@@ -898,6 +1170,36 @@ struct AdminSanitizeRequest {
     ovrpat: u32,
 }
 
+// MI v2.0, 6, Figure 136
+// Base v2.1, 5.1.25
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminSetFeaturesRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    dlen: u32,
+    #[deku(seek_from_current = "8")]
+    config: u32,
+    #[deku(pad_bytes_after = "16")]
+    value: u32,
+}
+
+// MI v2.0, 6, Figure 136
+// Base v2.1, Virtualization Management
+#[derive(Debug, DekuRead, Eq, PartialEq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct AdminVirtualizationManagementRequest {
+    nsid: u32,
+    #[deku(seek_from_current = "16")]
+    dofst: u32,
+    dlen: u32,
+    #[deku(seek_from_current = "8")]
+    config: u32,
+    #[deku(pad_bytes_after = "16")]
+    nr: u32,
+}
+
 // MI v2.0, 6, Figure 138
 #[derive(Debug, DekuRead, DekuWrite)]
 #[deku(endian = "little")]
@@ -946,3 +1248,35 @@ struct PcieConfigurationAccessRequest {
     #[deku(pad_bytes_after = "6")]
     offset: u16,
 }
+
+#[cfg(test)]
+mod test {
+    use crate::Encode;
+    use crate::nvme::mi::{NvmeManagementResponse, ResponseStatus};
+
+    #[test]
+    fn response_status_encoding() {
+        let cases = [
+            (ResponseStatus::MoreProcessingRequired, 0x01),
+            (ResponseStatus::VpdUpdatesExceeded, 0x20),
+            (ResponseStatus::PcieInaccessible, 0x21),
+            (
+                ResponseStatus::ManagementEndpointBufferClearedDueToSanitize,
+                0x22,
+            ),
+            (ResponseStatus::EnclosureServicesFailure, 0x23),
+            (ResponseStatus::EnclosureServicesTransferFailure, 0x24),
+            (ResponseStatus::EnclosureFailure, 0x25),
+            (ResponseStatus::EnclosureServicesTransferRefused, 0x26),
+            (ResponseStatus::UnsupportedEnclosureFunction, 0x27),
+            (ResponseStatus::EnclosureServicesNotAvailable, 0x28),
+            (ResponseStatus::EnclosureDegraded, 0x29),
+            (ResponseStatus::SanitizeInProgress, 0x2a),
+        ];
+
+        for (status, id) in cases {
+            let (buf, len) = NvmeManagementResponse { status }.encode().unwrap();
+            assert_eq!(&buf[..len], &[id, 0, 0, 0]);
+        }
+    }
+}
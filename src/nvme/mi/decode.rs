@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: GPL-3.0-only
+/*
+ * Copyright (c) 2025 Code Construct
+ */
+// Host-side decoding of NVMe-MI responses, for tests
+use deku::ctx::Endian;
+use deku::no_std_io::Cursor;
+use deku::reader::Reader;
+use deku::{DekuContainerRead, DekuReader};
+use flagset::FlagSet;
+
+use super::{
+    CompositeControllerStatusDataStructureResponse, ControllerHealthDataStructure,
+    ControllerHealthStatusChangedFlags, ControllerHealthStatusPollResponse, ControllerStatusFlags,
+    CriticalWarningFlags, MessageHeader, MessageType, NvmSubsystemHealthDataStructureResponse,
+    NvmSubsystemInformationResponse, NvmeMiDataStructureManagementResponse, ResponseStatus,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    IntegrityCheckFailed,
+    Malformed,
+    NotResponse,
+    Truncated,
+    UnexpectedMessageType,
+    Status(ResponseStatus),
+}
+
+impl From<deku::DekuError> for DecodeError {
+    fn from(value: deku::DekuError) -> Self {
+        match value {
+            deku::DekuError::Incomplete(_) => Self::Truncated,
+            _ => Self::Malformed,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Response<'a> {
+    nmimt: MessageType,
+    csi: bool,
+    body: &'a [u8],
+}
+
+impl<'a> Response<'a> {
+    pub fn csi(&self) -> bool {
+        self.csi
+    }
+
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    // MI v2.0, 4.1.2: Every response body begins with the status
+    fn status(&self, nmimt: MessageType) -> Result<(), DecodeError> {
+        if self.nmimt != nmimt {
+            return Err(DecodeError::UnexpectedMessageType);
+        }
+
+        let mut cursor = Cursor::new(self.body);
+        let mut reader = Reader::new(&mut cursor);
+        match ResponseStatus::from_reader_with_ctx(&mut reader, Endian::Little)? {
+            ResponseStatus::Success => Ok(()),
+            status => Err(DecodeError::Status(status)),
+        }
+    }
+}
+
+pub fn response(msg: &[u8]) -> Result<Response<'_>, DecodeError> {
+    let Some((msg, icv)) = msg
+        .len()
+        .checked_sub(4)
+        .and_then(|len| msg.split_at_checked(len))
+    else {
+        return Err(DecodeError::Truncated);
+    };
+
+    if icv != crate::MessageIntegrityCheck::default().icv(&[msg]) {
+        return Err(DecodeError::IntegrityCheckFailed);
+    }
+
+    let ((body, _), mh) = MessageHeader::from_bytes((msg, 0))?;
+
+    if !mh.ror() {
+        return Err(DecodeError::NotResponse);
+    }
+
+    let nmimt = mh.nmimt().map_err(|_| DecodeError::UnexpectedMessageType)?;
+
+    Ok(Response {
+        nmimt,
+        csi: mh.csi(),
+        body,
+    })
+}
+
+// MI v2.0, 5.6, Figure 108
+#[derive(Debug, PartialEq, Eq)]
+pub struct NvmSubsystemHealth {
+    pub nss: u8,
+    pub sw: u8,
+    pub ctemp: u8,
+    pub pldu: u8,
+    pub ccsf: u16,
+}
+
+pub fn subsystem_health_status_poll(rsp: &Response) -> Result<NvmSubsystemHealth, DecodeError> {
+    rsp.status(MessageType::NvmeMiCommand)?;
+
+    let Some(body) = rsp.body.get(4..) else {
+        return Err(DecodeError::Truncated);
+    };
+    let ((body, _), nvmshds) = NvmSubsystemHealthDataStructureResponse::from_bytes((body, 0))?;
+    let (_, ccs) = CompositeControllerStatusDataStructureResponse::from_bytes((body, 0))?;
+
+    Ok(NvmSubsystemHealth {
+        nss: nvmshds.nss,
+        sw: nvmshds.sw,
+        ctemp: nvmshds.ctemp,
+        pldu: nvmshds.pldu,
+        ccsf: ccs.ccsf,
+    })
+}
+
+// MI v2.0, 5.3, Figure 97
+#[derive(Debug, PartialEq, Eq)]
+pub struct ControllerHealth {
+    pub ctlid: u16,
+    pub csts: FlagSet<ControllerStatusFlags>,
+    pub ctemp: u16,
+    pub pdlu: u8,
+    pub spare: u8,
+    pub cwarn: FlagSet<CriticalWarningFlags>,
+    pub chsc: FlagSet<ControllerHealthStatusChangedFlags>,
+}
+
+impl From<ControllerHealthDataStructure> for ControllerHealth {
+    fn from(value: ControllerHealthDataStructure) -> Self {
+        Self {
+            ctlid: value.ctlid,
+            csts: value.csts.0,
+            ctemp: value.ctemp,
+            pdlu: value.pdlu,
+            spare: value.spare,
+            cwarn: value.cwarn.0,
+            chsc: value.chsc.0,
+        }
+    }
+}
+
+// Malformed if the response reports more than N entries
+pub fn controller_health_status_poll<const N: usize>(
+    rsp: &Response,
+) -> Result<heapless::Vec<ControllerHealth, N>, DecodeError> {
+    rsp.status(MessageType::NvmeMiCommand)?;
+
+    // Decode the fixed fields, leaving RENT to bound the entries
+    let ((body, _), chspr) = ControllerHealthStatusPollResponse::<0>::from_bytes((rsp.body, 0))?;
+
+    let mut cursor = Cursor::new(body);
+    let mut reader = Reader::new(&mut cursor);
+    let mut entries = heapless::Vec::new();
+    for _ in 0..chspr.rent {
+        let chds =
+            ControllerHealthDataStructure::from_reader_with_ctx(&mut reader, Endian::Little)?;
+        entries
+            .push(chds.into())
+            .map_err(|_| DecodeError::Malformed)?;
+    }
+
+    Ok(entries)
+}
+
+// MI v2.0, 5.7.1, Figure 112
+#[derive(Debug, PartialEq, Eq)]
+pub struct NvmSubsystemInformation {
+    pub nump: u8,
+    pub mjr: u8,
+    pub mnr: u8,
+    pub nnsc: u8,
+}
+
+pub fn nvm_subsystem_information(rsp: &Response) -> Result<NvmSubsystemInformation, DecodeError> {
+    rsp.status(MessageType::NvmeMiCommand)?;
+
+    let ((body, _), dsmr) = NvmeMiDataStructureManagementResponse::from_bytes((rsp.body, 0))?;
+
+    let Some(body) = body.get(..dsmr.rdl as usize) else {
+        return Err(DecodeError::Truncated);
+    };
+    let (_, nvmsi) = NvmSubsystemInformationResponse::from_bytes((body, 0))?;
+
+    Ok(NvmSubsystemInformation {
+        nump: nvmsi.nump,
+        mjr: nvmsi.mjr,
+        mnr: nvmsi.mnr,
+        nnsc: nvmsi.nnsc,
+    })
+}
@@ -9,83 +9,140 @@ use log::debug;
 use mctp::{AsyncRespChannel, MsgIC};
 
 use crate::{
-    CommandEffect, CommandEffectError, Controller, ControllerError, ControllerType, Discriminant,
-    MAX_CONTROLLERS, MAX_NAMESPACES, NamespaceId, NamespaceIdDisposition, SubsystemError,
+    CommandEffect, CommandEffectError, ControllerType, Discriminant, GenericController,
+    HandleError, NamespaceAttachmentError, NamespaceId, NamespaceIdDisposition, SubsystemError,
     nvme::{
         AdminFormatNvmConfiguration, AdminGetLogPageLidRequestType,
         AdminGetLogPageSupportedLogPagesResponse, AdminIdentifyActiveNamespaceIdListResponse,
         AdminIdentifyAllocatedNamespaceIdListResponse, AdminIdentifyCnsRequestType,
-        AdminIdentifyControllerResponse,
+        AdminIdentifyControllerResponse, AdminIdentifyIoCommandSetIndependentNamespaceResponse,
         AdminIdentifyNamespaceIdentificationDescriptorListResponse,
-        AdminIdentifyNvmIdentifyNamespaceResponse, AdminIoCqeGenericCommandStatus,
-        AdminIoCqeStatus, AdminIoCqeStatusType, AdminSanitizeConfiguration, ControllerListResponse,
-        LidSupportedAndEffectsDataStructure, LidSupportedAndEffectsFlags, LogPageAttributes,
-        NamespaceIdentifierType, SanitizeAction, SanitizeOperationStatus, SanitizeState,
-        SanitizeStateInformation, SanitizeStatus, SanitizeStatusLogPageResponse,
-        SmartHealthInformationLogPageResponse,
+        AdminIdentifyNvmIdentifyNamespaceResponse,
+        AdminIdentifyPrimaryControllerCapabilitiesResponse, AdminIoCqeGenericCommandStatus,
+        AdminIoCqeStatus, AdminIoCqeStatusType, AdminSanitizeConfiguration,
+        AdminVirtualizationManagementConfiguration, ChangedNamespaceListLogPageResponse,
+        CommandsSupportedAndEffectsLogPageResponse, ControllerListResponse, ControllerResourceType,
+        ControllerResourceTypes, DeviceSelfTestLogPageResponse, DeviceSelfTestResultDataStructure,
+        FirmwareSlotInformationLogPageResponse, LidSupportedAndEffectsDataStructure,
+        LidSupportedAndEffectsFlags, LogPageAttributes, NamespaceIdentifierType, SanitizeAction,
+        SanitizeOperationStatus, SanitizeState, SanitizeStateInformation, SanitizeStatus,
+        SanitizeStatusLogPageResponse, SecondaryControllerEntry, SecondaryControllerListResponse,
+        SmartHealthInformationLogPageResponse, VirtualizationManagementAction,
         mi::{
             AdminCommandRequestHeader, AdminCommandResponseHeader, AdminFormatNvmRequest,
             AdminNamespaceAttachmentRequest, AdminNamespaceManagementRequest, AdminSanitizeRequest,
-            CompositeControllerStatusDataStructureResponse, CompositeControllerStatusFlagSet,
-            ControllerFunctionAndReportingFlags, ControllerHealthDataStructure,
-            ControllerHealthStatusPollResponse, ControllerInformationResponse,
-            ControllerPropertyFlags, MessageType, NvmSubsystemHealthDataStructureResponse,
-            NvmSubsystemInformationResponse, NvmeManagementResponse, NvmeMiCommandRequestHeader,
-            NvmeMiCommandRequestType, NvmeMiDataStructureManagementResponse,
-            NvmeMiDataStructureRequestType, PcieCommandRequestHeader, PciePortDataResponse,
+            AdminVirtualizationManagementRequest, CompositeControllerStatusDataStructureResponse,
+            CompositeControllerStatusFlagSet, ControllerFunctionAndReportingFlags,
+            ControllerHealthDataStructure, ControllerHealthStatusPollResponse,
+            ControllerInformationResponse, ControllerPropertyFlags, MessageType,
+            NvmSubsystemHealthDataStructureResponse, NvmSubsystemInformationResponse,
+            NvmeManagementResponse, NvmeMiCommandRequestHeader, NvmeMiCommandRequestType,
+            NvmeMiDataStructureManagementResponse, NvmeMiDataStructureRequestType,
+            OptionallySupportedCommandDataStructure, OptionallySupportedCommandListResponse,
+            OptionallySupportedCommandType, PcieCommandRequestHeader, PciePortDataResponse,
             PortInformationResponse, TwoWirePortDataResponse,
         },
     },
-    pcie::PciDeviceFunctionConfigurationSpace,
-    wire::{WireString, WireVec},
+    wire::{WireFlagSet, WireString, WireVec},
 };
 
 use crate::Encode;
 use crate::RequestHandler;
 
 use super::{
-    AdminCommandRequestType, AdminGetLogPageRequest, AdminIdentifyRequest,
+    AdminAbortRequest, AdminAsynchronousEventRequest, AdminCommandRequestType,
+    AdminGetFeaturesRequest, AdminGetLogPageRequest, AdminIdentifyRequest, AdminKeepAliveRequest,
+    AdminSetFeaturesRequest, AsynchronousEventEnableListHeader,
+    AsynchronousEventSupportedDescriptor, GetAsynchronousEventResponse,
     GetHealthStatusChangeResponse, GetMctpTransmissionUnitSizeResponse,
     GetSmbusI2cFrequencyResponse, MessageHeader, NvmeMiConfigurationGetRequest,
     NvmeMiConfigurationIdentifierRequestType, NvmeMiConfigurationSetRequest,
-    NvmeMiDataStructureRequest, ResponseStatus,
+    NvmeMiDataStructureRequest, ParameterErrorLocation, ResponseStatus,
 };
 
-const ISCSI: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISCSI);
-const MAX_FRAGMENTS: usize = 6;
+#[cfg(feature = "crc-slice16")]
+type IscsiTable = crc::Table<16>;
+#[cfg(not(feature = "crc-slice16"))]
+type IscsiTable = crc::Table<1>;
+
+static ISCSI: crc::Crc<u32, IscsiTable> = crc::Crc::<u32, IscsiTable>::new(&crc::CRC_32_ISCSI);
+
+// MI v2.0, 3.1: The message integrity check covers the MCTP message type byte
+// (IC set, NVMe-MI), so the digest state after that byte is constant
+const ISCSI_MCTP_INITIAL: u32 = (crc::Crc::<u32, crc::NoTable>::new(&crc::CRC_32_ISCSI)
+    .checksum(&[0x80 | 0x04])
+    ^ crc::CRC_32_ISCSI.xorout)
+    .reverse_bits();
+
+// The largest responses gather the message header, the response header and
+// two data structures, to which the integrity check is appended. Allow some
+// headroom over that.
+const MAX_FRAGMENTS: usize = 8;
+
+impl crate::MessageIntegrityCheck {
+    // Absorb prefix ahead of each message
+    pub fn new(seed: u32, prefix: u8) -> Self {
+        let mut digest = ISCSI.digest_with_initial(seed);
+        digest.update(&[prefix]);
+        Self {
+            initial: (digest.finalize() ^ crc::CRC_32_ISCSI.xorout).reverse_bits(),
+        }
+    }
 
-async fn send_response(resp: &mut impl AsyncRespChannel, bufs: &[&[u8]]) {
-    let mut digest = ISCSI.digest();
-    digest.update(&[0x80 | 0x04]);
+    pub(crate) fn icv(&self, bufs: &[&[u8]]) -> [u8; 4] {
+        let mut digest = ISCSI.digest_with_initial(self.initial);
+        for s in bufs {
+            digest.update(s);
+        }
+        digest.finalize().to_le_bytes()
+    }
+}
 
-    for s in bufs {
-        digest.update(s);
+impl Default for crate::MessageIntegrityCheck {
+    fn default() -> Self {
+        Self {
+            initial: ISCSI_MCTP_INITIAL,
+        }
     }
-    let icv = digest.finalize().to_le_bytes();
+}
+
+// Fails only if the response cannot be assembled, in which case the caller's
+// error path can still report an Internal Error to the requester
+async fn send_response(
+    resp: &mut impl AsyncRespChannel,
+    mic: crate::MessageIntegrityCheck,
+    bufs: &[&[u8]],
+) -> Result<(), ResponseStatus> {
+    let icv = mic.icv(bufs);
 
     let Ok(mut bufs) = Vec::<&[u8], MAX_FRAGMENTS>::from_slice(bufs) else {
-        debug!("Failed to gather buffers into vec");
-        return;
+        debug!("Failed to gather {} buffers into vec", bufs.len());
+        return Err(ResponseStatus::InternalError);
     };
 
     if bufs.push(icv.as_slice()).is_err() {
         debug!("Failed to apply integrity check to response");
-        return;
+        return Err(ResponseStatus::InternalError);
     }
 
+    #[cfg(feature = "trace-responses")]
+    crate::ManagementEndpoint::trace_response(bufs.as_slice());
+
     if let Err(e) = resp.send_vectored(MsgIC(true), bufs.as_slice()).await {
         debug!("Failed to send NVMe-MI response: {e:?}");
     }
+
+    Ok(())
 }
 
 impl RequestHandler for MessageHeader {
     type Ctx = Self;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         app: A,
@@ -107,8 +164,15 @@ impl RequestHandler for MessageHeader {
                     Ok(((rest, _), ch)) => ch.handle(ch, mep, subsys, rest, resp, app).await,
                     Err(err) => {
                         debug!("Unable to parse NVMeMICommandHeader from message buffer: {err:?}");
-                        // TODO: This is a bad assumption: Can see DekuError::InvalidParam too
-                        Err(ResponseStatus::InvalidCommandSize)
+                        // MI v2.0, 5, Figure 68
+                        let pel = match rest.first() {
+                            // CONFIGID
+                            Some(0x03 | 0x04) => Some(ParameterErrorLocation::new(8, 0)),
+                            // DTYP
+                            Some(0x00) => Some(ParameterErrorLocation::new(11, 0)),
+                            _ => None,
+                        };
+                        Err(request_decode_status(err, pel))
                     }
                 }
             }
@@ -117,23 +181,25 @@ impl RequestHandler for MessageHeader {
                     Ok(((rest, _), ch)) => ch.handle(ch, mep, subsys, rest, resp, app).await,
                     Err(err) => {
                         debug!("Unable to parse AdminCommandHeader from message buffer: {err:?}");
-                        // TODO: This is a bad assumption: Can see DekuError::InvalidParam too
-                        Err(ResponseStatus::InvalidCommandSize)
+                        // MI v2.0, 6, Figure 136
+                        let pel = match rest.first() {
+                            // LID, CNS, SEL
+                            Some(0x02 | 0x06 | 0x0d | 0x15) => {
+                                Some(ParameterErrorLocation::new(44, 0))
+                            }
+                            _ => None,
+                        };
+                        Err(request_decode_status(err, pel))
                     }
                 }
             }
-            MessageType::PcieCommand => {
-                match &PcieCommandRequestHeader::from_bytes((rest, 0)) {
-                    Ok(((rest, _), ch)) => ch.handle(ch, mep, subsys, rest, resp, app).await,
-                    Err(err) => {
-                        debug!(
-                            "Unable to parse PcieCommandRequestHeader from message buffer: {err:?}"
-                        );
-                        // TODO: This is a bad assumption: Can see DekuError::InvalidParam too
-                        Err(ResponseStatus::InvalidCommandSize)
-                    }
+            MessageType::PcieCommand => match &PcieCommandRequestHeader::from_bytes((rest, 0)) {
+                Ok(((rest, _), ch)) => ch.handle(ch, mep, subsys, rest, resp, app).await,
+                Err(err) => {
+                    debug!("Unable to parse PcieCommandRequestHeader from message buffer: {err:?}");
+                    Err(request_decode_status(err, None))
                 }
-            }
+            },
             _ => {
                 debug!("Unimplemented NMINT: {:?}", ctx.nmimt());
                 Err(ResponseStatus::InternalError)
@@ -142,14 +208,28 @@ impl RequestHandler for MessageHeader {
     }
 }
 
+// Failing to decode a request of sufficient length indicates an unrecognised
+// identifier. Where the opcode is recognised, `pel` locates the identifier
+// selecting the command's parameters.
+fn request_decode_status(err: &DekuError, pel: Option<ParameterErrorLocation>) -> ResponseStatus {
+    match err {
+        DekuError::Incomplete(_) => ResponseStatus::InvalidCommandSize,
+        DekuError::Parse(_) | DekuError::IdVariantNotFound => match pel {
+            Some(pel) => ResponseStatus::InvalidParameter(pel),
+            None => ResponseStatus::InvalidCommandOpcode,
+        },
+        _ => ResponseStatus::InternalError,
+    }
+}
+
 impl RequestHandler for NvmeMiCommandRequestHeader {
     type Ctx = Self;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         app: A,
@@ -165,52 +245,70 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
             }
             NvmeMiCommandRequestType::NvmSubsystemHealthStatusPoll(shsp) => {
                 // 5.6, Figure 108, v2.0
+                //
+                // The request carries no selectors for the response content:
+                // Figure 106 defines only CS, so both the NVM Subsystem Health
+                // Data Structure and the Composite Controller Status are always
+                // returned.
                 if !rest.is_empty() {
                     debug!("Lost coherence decoding {:?}", ctx.opcode);
                     return Err(ResponseStatus::InvalidCommandSize);
                 }
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                if let Some(pel) = shsp.reserved() {
+                    debug!("Reserved fields set in NVM Subsystem Health Status Poll");
+                    return Err(ResponseStatus::InvalidParameter(pel));
+                }
+
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
                 let mr = NvmeManagementResponse {
                     status: ResponseStatus::Success,
                 }
                 .encode()?;
 
-                // Implementation-specific strategy is to pick the first controller.
-                let ctlr = subsys
-                    .ctlrs
-                    .first()
-                    .expect("Device needs at least one controller");
+                // Implementation-specific strategy is to report link state for
+                // the port of the first controller.
+                let Some(ctlr) = subsys.ctlrs.first() else {
+                    debug!("No controllers available for health status");
+                    return Err(ResponseStatus::InternalError);
+                };
 
                 let Some(port) = subsys.ports.iter().find(|p| p.id == ctlr.port) else {
-                    panic!(
+                    debug!(
                         "Inconsistent port association for controller {:?}: {:?}",
                         ctlr.id, ctlr.port
                     );
+                    return Err(ResponseStatus::InternalError);
                 };
 
-                let crate::PortType::Pcie(pprt) = port.typ else {
-                    panic!("Non-PCIe port associated with controller {:?}", ctlr.id);
+                // An inactive or non-PCIe port has no link to report
+                let p0la = match port.typ {
+                    crate::PortType::Pcie(pprt) => pprt.link_active(),
+                    _ => false,
                 };
 
-                // Derive ASCBT from spare vs capacity
-                if ctlr.spare > ctlr.capacity {
-                    debug!(
-                        "spare capacity {} exceeds drive capacity {}",
-                        ctlr.spare, ctlr.capacity
-                    );
-                    return Err(ResponseStatus::InternalError);
+                for ctlr in &subsys.ctlrs {
+                    // Derive ASCBT from spare vs capacity
+                    if ctlr.spare > ctlr.capacity {
+                        debug!(
+                            "spare capacity {} exceeds drive capacity {}",
+                            ctlr.spare, ctlr.capacity
+                        );
+                        return Err(ResponseStatus::InternalError);
+                    }
                 }
 
-                // Derive TTC from operating range comparison
-                assert!(ctlr.temp_range.kind == crate::UnitKind::Kelvin);
+                // Report the worst case across all controllers
+                let Some(health) = subsys.aggregate_health() else {
+                    debug!("No controllers available for health status");
+                    return Err(ResponseStatus::InternalError);
+                };
+                let cwarn = health.cwarn;
 
                 // Derive CTEMP from controller temperature via conversions
                 // Clamp to Figure 108, NVMe MI v2.0 requirements
-                let clamped = ctlr
-                    .temp
-                    .clamp(ctlr.temp_range.lower, ctlr.temp_range.upper);
+                let clamped = health.temp;
 
                 // Convert to celcius from kelvin
                 let celcius: i32 = clamped as i32 - 273;
@@ -223,25 +321,21 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
                 };
                 debug_assert!(ctemp <= u8::MAX.into());
 
-                // Derive PLDU from write age and expected lifespan
-                let pdlu = core::cmp::min(255, 100 * ctlr.write_age / ctlr.write_lifespan);
-
                 let nvmshds = NvmSubsystemHealthDataStructureResponse {
                     nss: (subsys.health.nss.atf as u8) << 7
                         | (subsys.health.nss.sfm as u8) << 6
                         | (subsys.health.nss.df as u8) << 5
                         | (subsys.health.nss.rnr as u8) << 4
-                        | ((pprt.cls != crate::nvme::mi::PcieLinkSpeed::Inactive) as u8) << 3 // P0LA
+                        | (p0la as u8) << 3 // P0LA
                         | (false as u8) << 2, // P1LA
-                    #[allow(clippy::nonminimal_bool)]
-                    sw: (!false as u8) << 5 // PMRRO
-                        | (!false as u8) << 4 // VMBF
-                        | (!ctlr.ro as u8) << 3 // AMRO
-                        | (!subsys.health.nss.rd as u8) << 2 // NDR
-                        | (!(ctlr.temp_range.lower <= ctlr.temp && ctlr.temp <= ctlr.temp_range.upper) as u8) << 1 // TTC
-                        | (!((100 * ctlr.spare / ctlr.capacity) < ctlr.spare_range.lower) as u8),
+                    sw: (!cwarn.contains(crate::nvme::mi::CriticalWarningFlags::Pmre) as u8) << 5 // PMRRO
+                        | (!cwarn.contains(crate::nvme::mi::CriticalWarningFlags::Vmbf) as u8) << 4 // VMBF
+                        | (!cwarn.contains(crate::nvme::mi::CriticalWarningFlags::Ro) as u8) << 3 // AMRO
+                        | (!cwarn.contains(crate::nvme::mi::CriticalWarningFlags::Rd) as u8) << 2 // NDR
+                        | (cwarn.contains(crate::nvme::mi::CriticalWarningFlags::Taut) as u8) << 1 // TTC
+                        | (!cwarn.contains(crate::nvme::mi::CriticalWarningFlags::St) as u8),
                     ctemp: ctemp as u8,
-                    pldu: pdlu as u8,
+                    pldu: health.pldu,
                 }
                 .encode()?;
 
@@ -250,13 +344,11 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
                 }
                 .encode()?;
 
-                // CS: See Figure 106, NVMe MI v2.0
-                if (shsp.dword1 & (1u32 << 31)) != 0 {
+                if shsp.cs() {
                     mep.ccsf.0.clear();
                 }
 
-                send_response(resp, &[&mh.0, &mr.0, &nvmshds.0, &ccs.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &mr.0, &nvmshds.0, &ccs.0]).await
             }
             NvmeMiCommandRequestType::ControllerHealthStatusPoll(req) => {
                 // MI v2.0, 5.3
@@ -283,8 +375,8 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
                     return Err(ResponseStatus::InternalError);
                 }
 
-                assert!(MAX_CONTROLLERS <= u8::MAX as usize);
-                if req.maxrent < MAX_CONTROLLERS as u8 {
+                const { assert!(CTLRS <= u8::MAX as usize) };
+                if req.maxrent < CTLRS as u8 {
                     debug!("TODO: Implement response entry constraint");
                     return Err(ResponseStatus::InternalError);
                 }
@@ -294,57 +386,37 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
                     return Err(ResponseStatus::InternalError);
                 }
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
-                let mut chspr = ControllerHealthStatusPollResponse {
+                let mut chspr = ControllerHealthStatusPollResponse::<CTLRS> {
                     status: ResponseStatus::Success,
                     rent: 0,
                     body: WireVec::new(),
                 };
 
-                for ctlr in &subsys.ctlrs {
+                for ctlr in &mut subsys.ctlrs {
                     chspr
                         .body
                         .push(ControllerHealthDataStructure {
                             ctlid: ctlr.id.0,
-                            csts: ctlr.csts.into(),
+                            csts: {
+                                let mut csts: WireFlagSet<_> = ctlr.csts.into();
+                                csts.0 |= ctlr.csts_latched;
+                                csts
+                            },
                             ctemp: ctlr.temp,
                             pdlu: core::cmp::min(255, 100 * ctlr.write_age / ctlr.write_lifespan)
                                 as u8,
-                            spare: <u8>::try_from(100 * ctlr.spare / ctlr.capacity)
-                                .map_err(|_| ResponseStatus::InternalError)?
-                                .clamp(0, 100),
-                            cwarn: {
-                                let mut fs = FlagSet::empty();
-
-                                if ctlr.spare < ctlr.spare_range.lower {
-                                    fs |= crate::nvme::mi::CriticalWarningFlags::St;
-                                }
-
-                                if ctlr.temp < ctlr.temp_range.lower
-                                    || ctlr.temp > ctlr.temp_range.upper
-                                {
-                                    fs |= crate::nvme::mi::CriticalWarningFlags::Taut;
-                                }
-
-                                // TODO: RD
-
-                                if ctlr.ro {
-                                    fs |= crate::nvme::mi::CriticalWarningFlags::Ro;
-                                }
-
-                                // TODO: VMBF
-                                // TODO: PMRRO
-
-                                fs.into()
-                            },
+                            spare: ctlr.spare_percent().ok_or(ResponseStatus::InternalError)?,
+                            cwarn: ctlr.cwarn().into(),
                             chsc: {
                                 let mecs = &mut mep.mecss[ctlr.id.0 as usize];
                                 let fs = mecs.chscf;
 
                                 if req.properties.0.contains(ControllerPropertyFlags::Ccf) {
                                     mecs.chscf.clear();
-                                    // TODO: Clear NAC, FA, TCIDA in controller health
+                                    ctlr.csts_latched.clear();
+                                    mecs.csts_latched.clear();
                                 }
 
                                 fs.into()
@@ -358,8 +430,7 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
                 chspr.update()?;
                 let chspr = chspr.encode()?;
 
-                send_response(resp, &[&mh.0, &chspr.0[..chspr.1]]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &chspr.0[..chspr.1]]).await
             }
             NvmeMiCommandRequestType::ConfigurationSet(cid) => {
                 cid.handle(ctx, mep, subsys, rest, resp, app).await
@@ -367,9 +438,16 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
             NvmeMiCommandRequestType::ConfigurationGet(cid) => {
                 cid.handle(ctx, mep, subsys, rest, resp, app).await
             }
-            _ => {
-                debug!("Unimplemented OPCODE: {:?}", ctx.opcode);
-                Err(ResponseStatus::InternalError)
+            NvmeMiCommandRequestType::VpdRead
+            | NvmeMiCommandRequestType::VpdWrite
+            | NvmeMiCommandRequestType::Reset
+            | NvmeMiCommandRequestType::SesReceive
+            | NvmeMiCommandRequestType::SesSend
+            | NvmeMiCommandRequestType::ManagementEndpointBufferRead
+            | NvmeMiCommandRequestType::ManagementEndpointBufferWrite
+            | NvmeMiCommandRequestType::Shutdown => {
+                debug!("Unsupported OPCODE: {:?}", ctx.opcode);
+                Err(ResponseStatus::InvalidCommandOpcode)
             }
         }
     }
@@ -378,11 +456,11 @@ impl RequestHandler for NvmeMiCommandRequestHeader {
 impl RequestHandler for NvmeMiConfigurationSetRequest {
     type Ctx = NvmeMiCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         mut app: A,
@@ -392,9 +470,9 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
         C: AsyncRespChannel,
     {
         match &self.body {
-            NvmeMiConfigurationIdentifierRequestType::Reserved => {
-                Err(ResponseStatus::InvalidParameter)
-            }
+            NvmeMiConfigurationIdentifierRequestType::Reserved => Err(
+                ResponseStatus::InvalidParameter(ParameterErrorLocation::new(8, 0)),
+            ),
             NvmeMiConfigurationIdentifierRequestType::SmbusI2cFrequency(sifr) => {
                 if !rest.is_empty() {
                     debug!("Lost synchronisation when decoding ConfigurationSet SmbusI2cFrequency");
@@ -403,33 +481,52 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
 
                 let Some(port) = subsys.ports.get_mut(sifr.dw0_portid as usize) else {
                     debug!("Unrecognised port ID: {}", sifr.dw0_portid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
                 };
 
                 let crate::PortType::TwoWire(twprt) = &mut port.typ else {
                     debug!("Port {} is not a TwoWire port: {:?}", sifr.dw0_portid, port);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
+                };
+
+                if sifr.dw0_sfreq & 0xf0 != 0 {
+                    debug!("Reserved bits set in SFREQ: {:#x}", sifr.dw0_sfreq);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(9, 4),
+                    ));
+                }
+
+                let Ok(sfreq) = super::SmbusFrequency::try_from(sifr.dw0_sfreq) else {
+                    debug!("Reserved SMBus frequency: {:#x}", sifr.dw0_sfreq);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(9, 0),
+                    ));
                 };
 
-                if sifr.dw0_sfreq > twprt.msmbfreq {
-                    debug!("Unsupported SMBus frequency: {:?}", sifr.dw0_sfreq);
-                    return Err(ResponseStatus::InvalidParameter);
+                if sfreq > twprt.msmbfreq {
+                    debug!("Unsupported SMBus frequency: {:?}", sfreq);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(9, 0),
+                    ));
                 }
 
                 app(CommandEffect::SetSmbusFreq {
                     port_id: port.id,
-                    freq: sifr.dw0_sfreq,
+                    freq: sfreq,
                 })
                 .await?;
-                twprt.smbfreq = sifr.dw0_sfreq;
+                twprt.smbfreq = sfreq;
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
                 // Success
                 let status = [0u8; 4];
 
-                send_response(resp, &[&mh.0, &status]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &status]).await
             }
             NvmeMiConfigurationIdentifierRequestType::HealthStatusChange(hscr) => {
                 if !rest.is_empty() {
@@ -444,18 +541,19 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
                         "Invalid composite controller status flags in request: {}",
                         hscr.dw1
                     );
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(12, 0),
+                    ));
                 };
                 let clear: super::CompositeControllerStatusFlagSet = clear.into();
                 mep.ccsf.0 -= clear.0;
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
                 // Success
                 let status = [0u8; 4];
 
-                send_response(resp, &[&mh.0, &status]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &status]).await
             }
             NvmeMiConfigurationIdentifierRequestType::MctpTransmissionUnitSize(mtusr) => {
                 if !rest.is_empty() {
@@ -467,9 +565,24 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
 
                 let Some(port) = subsys.ports.get_mut(mtusr.dw0_portid as usize) else {
                     debug!("Unrecognised port ID: {}", mtusr.dw0_portid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
                 };
 
+                // MI v2.0, 5.2.3: MTUS must lie within the MCTP baseline and MMTUS
+                if !(crate::MCTP_BASELINE_MTU..=port.mmtus).contains(&mtusr.dw1_mtus) {
+                    debug!(
+                        "MTU {} outside supported range [{}, {}]",
+                        mtusr.dw1_mtus,
+                        crate::MCTP_BASELINE_MTU,
+                        port.mmtus
+                    );
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(12, 0),
+                    ));
+                }
+
                 app(CommandEffect::SetMtu {
                     port_id: port.id,
                     mtus: mtusr.dw1_mtus as usize,
@@ -477,13 +590,114 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
                 .await?;
                 port.mtus = mtusr.dw1_mtus;
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
+                let status = [0u8; 4];
+
+                send_response(resp, mep.mic, &[&mh.0, &status]).await
+            }
+            NvmeMiConfigurationIdentifierRequestType::AsynchronousEvent(aer) => {
+                // MI v2.0, 5.2.4
+                if let Some(pel) = aer.reserved() {
+                    debug!("Reserved fields set in ConfigurationSet AsynchronousEvent");
+                    return Err(ResponseStatus::InvalidParameter(pel));
+                }
+
+                let Ok(((data, _), aeelh)) =
+                    AsynchronousEventEnableListHeader::from_bytes((rest, 0))
+                else {
+                    debug!("Failed to decode AE Enable List header");
+                    return Err(ResponseStatus::InvalidCommandInputDataSize);
+                };
+
+                if aeelh.aeelver != 0 {
+                    debug!("Unsupported AE Enable List version: {}", aeelh.aeelver);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(16, 0),
+                    ));
+                }
+
+                if aeelh.aeelhl != 5 {
+                    debug!("Invalid AE Enable List header length: {}", aeelh.aeelhl);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(17, 0),
+                    ));
+                }
+
+                if aeelh.aeeltl as usize != rest.len() {
+                    debug!(
+                        "AE Enable List total length {} does not match request data size {}",
+                        aeelh.aeeltl,
+                        rest.len()
+                    );
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(18, 0),
+                    ));
+                }
+
+                if data.len() != 2 * aeelh.numaee as usize {
+                    debug!(
+                        "AE Enable List data size {} does not match {} descriptors",
+                        data.len(),
+                        aeelh.numaee
+                    );
+                    return Err(ResponseStatus::InvalidCommandInputDataSize);
+                }
+
+                // MI v2.0, 5.7.2, Figure 114: AEMs are only delivered through
+                // ports reporting AEMS
+                let aems = subsys
+                    .ports
+                    .iter()
+                    .find(|p| p.id == mep.port)
+                    .is_some_and(|p| p.caps.aems);
+
+                // Validate the entire list before applying any of it
+                let mut aee = mep.aee;
+                for (idx, aeed) in data.chunks_exact(2).enumerate() {
+                    let byte = 21 + 2 * idx as u16;
+                    let (aeedl, aeei) = (aeed[0], aeed[1]);
+
+                    if aeedl != 2 {
+                        debug!("Invalid AE Enable Descriptor length: {aeedl}");
+                        return Err(ResponseStatus::InvalidParameter(
+                            ParameterErrorLocation::new(byte, 0),
+                        ));
+                    }
+
+                    if aeei & (1 << 6) != 0 {
+                        debug!("Reserved bit set in AE Enable Information: {aeei:#x}");
+                        return Err(ResponseStatus::InvalidParameter(
+                            ParameterErrorLocation::new(byte + 1, 6),
+                        ));
+                    }
+
+                    let aei = 1u64 << (aeei & 0x3f);
+                    if mep.aes & aei == 0 {
+                        debug!("Unsupported AE identifier: {}", aeei & 0x3f);
+                        return Err(ResponseStatus::InvalidParameter(
+                            ParameterErrorLocation::new(byte + 1, 0),
+                        ));
+                    }
+
+                    if aeei & (1 << 7) != 0 {
+                        if !aems {
+                            debug!("AE enable requested on port without AEMS");
+                            return Err(ResponseStatus::InvalidParameter(
+                                ParameterErrorLocation::new(byte + 1, 7),
+                            ));
+                        }
+                        aee |= aei;
+                    } else {
+                        aee &= !aei;
+                    }
+                }
+                mep.aee = aee;
+
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
                 let status = [0u8; 4];
 
-                send_response(resp, &[&mh.0, &status]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &status]).await
             }
-            NvmeMiConfigurationIdentifierRequestType::AsynchronousEvent => todo!(),
         }
     }
 }
@@ -491,11 +705,11 @@ impl RequestHandler for NvmeMiConfigurationSetRequest {
 impl RequestHandler for NvmeMiConfigurationGetRequest {
     type Ctx = NvmeMiCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -505,9 +719,9 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
         C: AsyncRespChannel,
     {
         match &self.body {
-            NvmeMiConfigurationIdentifierRequestType::Reserved => {
-                Err(ResponseStatus::InvalidParameter)
-            }
+            NvmeMiConfigurationIdentifierRequestType::Reserved => Err(
+                ResponseStatus::InvalidParameter(ParameterErrorLocation::new(8, 0)),
+            ),
             NvmeMiConfigurationIdentifierRequestType::SmbusI2cFrequency(sifr) => {
                 if !rest.is_empty() {
                     debug!("Lost synchronisation when decoding ConfigurationGet SMBusI2CFrequency");
@@ -516,15 +730,19 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
 
                 let Some(port) = subsys.ports.get(sifr.dw0_portid as usize) else {
                     debug!("Unrecognised port ID: {}", sifr.dw0_portid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
                 };
 
                 let crate::PortType::TwoWire(twprt) = port.typ else {
                     debug!("Port {} is not a TwoWire port: {:?}", sifr.dw0_portid, port);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
                 };
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
                 let fr = GetSmbusI2cFrequencyResponse {
                     status: ResponseStatus::Success,
@@ -532,8 +750,7 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &fr.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &fr.0]).await
             }
             NvmeMiConfigurationIdentifierRequestType::HealthStatusChange(_) => {
                 // MI v2.0, 5.1.2
@@ -544,14 +761,13 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
                     return Err(ResponseStatus::InvalidCommandSize);
                 }
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
                 let hscr = GetHealthStatusChangeResponse {
                     status: ResponseStatus::Success,
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &hscr.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &hscr.0]).await
             }
             NvmeMiConfigurationIdentifierRequestType::MctpTransmissionUnitSize(mtusr) => {
                 if !rest.is_empty() {
@@ -563,21 +779,60 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
 
                 let Some(port) = subsys.ports.get(mtusr.dw0_portid as usize) else {
                     debug!("Unrecognised port ID: {}", mtusr.dw0_portid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(11, 0),
+                    ));
                 };
 
-                let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
                 let fr = GetMctpTransmissionUnitSizeResponse {
                     status: ResponseStatus::Success,
-                    mr_mtus: port.mtus,
+                    mr_mtus: port.effective_mtus(),
+                }
+                .encode()?;
+
+                send_response(resp, mep.mic, &[&mh.0, &fr.0]).await
+            }
+            NvmeMiConfigurationIdentifierRequestType::AsynchronousEvent(aer) => {
+                // MI v2.0, 5.1.4
+                if !rest.is_empty() {
+                    debug!("Lost synchronisation when decoding ConfigurationGet AsynchronousEvent");
+                    return Err(ResponseStatus::InvalidCommandSize);
+                }
+
+                if let Some(pel) = aer.reserved() {
+                    debug!("Reserved fields set in ConfigurationGet AsynchronousEvent");
+                    return Err(ResponseStatus::InvalidParameter(pel));
+                }
+
+                let mut aesd = WireVec::new();
+                for aei in (0..64u8).filter(|aei| mep.aes & (1 << aei) != 0) {
+                    let aese = (mep.aee & (1 << aei) != 0) as u8;
+                    aesd.push(AsynchronousEventSupportedDescriptor {
+                        aesdl: 2,
+                        aesi: aese << 7 | aei,
+                    })
+                    .map_err(|_| {
+                        debug!("Failed to push AsynchronousEventSupportedDescriptor");
+                        ResponseStatus::InternalError
+                    })?;
+                }
+
+                let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
+                let numaes = aesd.len() as u8;
+                let aer = GetAsynchronousEventResponse {
+                    status: ResponseStatus::Success,
+                    aeslver: 0,
+                    aeslhl: 5,
+                    aesltl: 5 + 2 * numaes as u16,
+                    numaes,
+                    aesd,
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &fr.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &aer.0[..aer.1]]).await
             }
-            NvmeMiConfigurationIdentifierRequestType::AsynchronousEvent => todo!(),
         }
     }
 }
@@ -585,11 +840,11 @@ impl RequestHandler for NvmeMiConfigurationGetRequest {
 impl RequestHandler for NvmeMiDataStructureRequest {
     type Ctx = NvmeMiCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -603,7 +858,7 @@ impl RequestHandler for NvmeMiDataStructureRequest {
             return Err(ResponseStatus::InvalidCommandInputDataSize);
         }
 
-        let mh = MessageHeader::respond(MessageType::NvmeMiCommand).encode()?;
+        let mh = MessageHeader::respond(MessageType::NvmeMiCommand, mep.csi).encode()?;
 
         match self.body {
             NvmeMiDataStructureRequestType::NvmSubsystemInformation => {
@@ -630,13 +885,14 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &dsmr.0, &nvmsi.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &nvmsi.0]).await
             }
             NvmeMiDataStructureRequestType::PortInformation => {
                 let Some(port) = subsys.ports.iter().find(|p| p.id.0 == self.portid) else {
-                    // TODO: Propagate PEL
-                    return Err(ResponseStatus::InvalidParameter);
+                    debug!("Unrecognised port ID: {}", self.portid);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(10, 0),
+                    ));
                 };
                 let pi = PortInformationResponse {
                     // FIXME: Change prttyp to crate::nvme::mi::PortType
@@ -651,10 +907,10 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                     crate::PortType::Pcie(pprt) => {
                         let ppd = PciePortDataResponse {
                             pciemps: pprt.mps.into(),
-                            pcieslsv: 0x3fu8,
-                            pciecls: pprt.cls.into(),
+                            pcieslsv: pprt.supported_link_speeds(),
+                            pciecls: pprt.current_link_speed(),
                             pciemlw: pprt.mlw.into(),
-                            pcienlw: pprt.nlw.into(),
+                            pcienlw: pprt.negotiated_link_width(),
                             pciepn: port.id.0,
                         }
                         .encode()?;
@@ -666,8 +922,7 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                         }
                         .encode()?;
 
-                        send_response(resp, &[&mh.0, &dsmr.0, &pi.0, &ppd.0]).await;
-                        Ok(())
+                        send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &pi.0, &ppd.0]).await
                     }
                     crate::PortType::TwoWire(twprt) => {
                         let twpd = TwoWirePortDataResponse {
@@ -686,12 +941,20 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                         }
                         .encode()?;
 
-                        send_response(resp, &[&mh.0, &dsmr.0, &pi.0, &twpd.0]).await;
-                        Ok(())
+                        send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &pi.0, &twpd.0]).await
                     }
-                    _ => {
-                        debug!("Unimplemented port type: {:?}", port.typ);
-                        Err(ResponseStatus::InternalError)
+                    crate::PortType::Inactive => {
+                        // MI v2.0, 5.7.2: Port-specific data is reserved for inactive ports
+                        let ipd = [0u8; 24];
+
+                        debug_assert!((pi.0.len() + ipd.len()) <= u16::MAX as usize);
+                        let dsmr = NvmeMiDataStructureManagementResponse {
+                            status: ResponseStatus::Success,
+                            rdl: (pi.0.len() + ipd.len()) as u16,
+                        }
+                        .encode()?;
+
+                        send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &pi.0, &ipd]).await
                     }
                 }
             }
@@ -715,23 +978,17 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                     };
                 }
 
-                // NVMeSubsystemInformation and PortInformation are defined to
-                // be a minimum of 32 bytes in v2.0 of the NVMe specification.
-                // ControllerList in the NVMe MI v2.0 specification defers to
-                // Figure 137 in v2.1 of the NVMe base specification, which
-                // says "Unused entries are zero-filled". The motivation
-                // for padding out to 32 bytes for NVMeSubsystemInformation
-                // and PortInformation messages is unclear wrt whether
-                // ControllerList should be similarly padded or dynamically
-                // sized, given it can extend to 4096 bytes.
-                //
-                // Assume it's always dynamically sized appropriate for the
-                // requested controllers for now. mi-mctp seems okay with this.
-                //
-                // Note that for zero or even numbers of controllers in the
-                // response the MIC falls out of natural alignment.
+                // Unlike NVMeSubsystemInformation and PortInformation, which
+                // MI v2.0 defines with a fixed 32 byte size, ControllerList
+                // uses the Base v2.1 Controller List format (Figure 137).
+                // There the 4096 byte size and zero-filled entries describe
+                // the Identify data buffer, while NUMIDS bounds the meaningful
+                // content. RDL therefore covers only NUMIDS and the populated
+                // identifiers. The MIC follows the data directly, so it is off
+                // DWORD alignment when the list holds zero or an even number
+                // of identifiers.
                 cl.update()?;
-                let cl = cl.encode()?;
+                let cl = cl.encode_into(&mut mep.scratch)?;
                 let rdl = cl.1 as u16;
 
                 let dsmr = NvmeMiDataStructureManagementResponse {
@@ -740,13 +997,14 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &dsmr.0, &cl.0[..cl.1]]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &cl.0[..cl.1]]).await
             }
             NvmeMiDataStructureRequestType::ControllerInformation => {
                 let Some(ctlr) = subsys.ctlrs.iter().find(|c| c.id.0 == self.ctrlid) else {
                     debug!("Unknown controller ID: {:?}", self.ctrlid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(8, 0),
+                    ));
                 };
 
                 let Some(port) = subsys.ports.iter().find(|p| p.id == ctlr.port) else {
@@ -763,11 +1021,12 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                 let ci = ControllerInformationResponse {
                     portid: ctlr.port.0,
                     prii: 1,
-                    pri: pprt.b << 8 | pprt.d << 4 | pprt.f,
-                    pcivid: subsys.info.pci_vid,
-                    pcidid: subsys.info.pci_did,
-                    pcisvid: subsys.info.pci_svid,
-                    pcisdid: subsys.info.pci_sdid,
+                    // MI v2.0, 5.7.4, Figure 117: Bus (15:8), Device (7:3), Function (2:0)
+                    pri: pprt.b << 8 | pprt.d << 3 | ctlr.func as u16,
+                    pcivid: ctlr.pcie.vid(),
+                    pcidid: ctlr.pcie.did(),
+                    pcisvid: ctlr.pcie.svid(),
+                    pcisdid: ctlr.pcie.sdid(),
                     pciesn: pprt.seg,
                 }
                 .encode()?;
@@ -779,8 +1038,81 @@ impl RequestHandler for NvmeMiDataStructureRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &dsmr.0, &ci.0]).await;
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &ci.0]).await
+            }
+            NvmeMiDataStructureRequestType::OptionallySupportedCommandList => {
+                let Some(ctlr) = subsys.ctlrs.iter().find(|c| c.id.0 == self.ctrlid) else {
+                    debug!("Unknown controller ID: {:?}", self.ctrlid);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(8, 0),
+                    ));
+                };
+
+                use crate::nvme::OptionalAdminCommandSupport as Oacs;
+                use OptionallySupportedCommandType as T;
+
+                // Discovery controllers support none of the optional admin
+                // commands, and Format NVM requires an I/O controller
+                let admin = ctlr.cntrltype != ControllerType::Discovery;
+                let io = ctlr.cntrltype == ControllerType::Io;
+                let sanicap = subsys.sanicap;
+
+                let mut oscl = OptionallySupportedCommandListResponse::new();
+                for (supported, cmdtype, opc) in [
+                    (
+                        admin && ctlr.oacs.contains(Oacs::Nms),
+                        T::NvmeAdminCommand,
+                        0x0d,
+                    ),
+                    (
+                        admin && ctlr.oacs.contains(Oacs::Nms),
+                        T::NvmeAdminCommand,
+                        0x15,
+                    ),
+                    (
+                        admin && ctlr.oacs.contains(Oacs::Vms),
+                        T::NvmeAdminCommand,
+                        0x1c,
+                    ),
+                    (
+                        io && ctlr.oacs.contains(Oacs::Fnvms),
+                        T::NvmeAdminCommand,
+                        0x80,
+                    ),
+                    (
+                        admin && (sanicap.ces || sanicap.bes || sanicap.ows),
+                        T::NvmeAdminCommand,
+                        0x84,
+                    ),
+                    // PCIe Configuration Read and Write
+                    (true, T::PcieCommand, 0x00),
+                    (true, T::PcieCommand, 0x01),
+                ] {
+                    if !supported {
+                        continue;
+                    }
+
+                    let cmd = OptionallySupportedCommandDataStructure {
+                        cmdtype: cmdtype.id(),
+                        opc,
+                    };
+                    if oscl.cmds.push(cmd).is_err() {
+                        debug!("Failed to push optionally supported command {opc:#04x}");
+                        return Err(ResponseStatus::InternalError);
+                    }
+                }
+
+                oscl.update()?;
+                let oscl = oscl.encode()?;
+
+                debug_assert!(oscl.1 <= u16::MAX as usize);
+                let dsmr = NvmeMiDataStructureManagementResponse {
+                    status: ResponseStatus::Success,
+                    rdl: oscl.1 as u16,
+                }
+                .encode()?;
+
+                send_response(resp, mep.mic, &[&mh.0, &dsmr.0, &oscl.0[..oscl.1]]).await
             }
             _ => {
                 debug!("Unimplemented DTYP: {:?}", self.dtyp);
@@ -793,11 +1125,11 @@ impl RequestHandler for NvmeMiDataStructureRequest {
 impl RequestHandler for AdminCommandRequestHeader {
     type Ctx = Self;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         app: A,
@@ -808,10 +1140,44 @@ impl RequestHandler for AdminCommandRequestHeader {
     {
         debug!("{self:x?}");
 
-        // ISH
-        if ctx.cflgs & 4 != 0 {
-            debug!("Support ignore shutdown state");
-            return Err(ResponseStatus::InternalError);
+        // MI v2.0, 6, Figure 136, ISH
+        if ctx.cflgs & 4 == 0
+            && let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize)
+            && !(ctlr.csts & crate::nvme::ControllerStatusFlags::ShstReserved).is_empty()
+        {
+            debug!(
+                "Rejecting command for CTLID {} in shutdown state",
+                ctx.ctlid
+            );
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::CommandSequenceError,
+                ),
+            )
+            .await;
+        }
+
+        // Base v2.1, 5.1.13.2.1, Figure 312, MDTS
+        if let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize)
+            && let Some(dlen) = admin_data_length(&self.op)
+            && !ctlr.transfer_permitted(dlen)
+        {
+            debug!(
+                "DLEN {dlen} exceeds MDTS {} for CTLID {}",
+                ctlr.mdts, ctx.ctlid
+            );
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
         }
 
         match &self.op {
@@ -833,13 +1199,28 @@ impl RequestHandler for AdminCommandRequestHeader {
             AdminCommandRequestType::Sanitize(req) => {
                 req.handle(ctx, mep, subsys, rest, resp, app).await
             }
+            AdminCommandRequestType::VirtualizationManagement(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
+            AdminCommandRequestType::SetFeatures(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
+            AdminCommandRequestType::GetFeatures(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
+            AdminCommandRequestType::Abort(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
+            AdminCommandRequestType::AsynchronousEventRequest(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
+            AdminCommandRequestType::KeepAlive(req) => {
+                req.handle(ctx, mep, subsys, rest, resp, app).await
+            }
             AdminCommandRequestType::DeleteIoSubmissionQueue
             | AdminCommandRequestType::CreateIoSubmissionQueue
             | AdminCommandRequestType::DeleteIoCompletionQueue
             | AdminCommandRequestType::CreateIoCompletionQueue
-            | AdminCommandRequestType::Abort
-            | AdminCommandRequestType::AsynchronousEventRequest
-            | AdminCommandRequestType::KeepAlive
             | AdminCommandRequestType::DirectiveSend
             | AdminCommandRequestType::DirectiveReceive
             | AdminCommandRequestType::NvmeMiSend
@@ -862,69 +1243,143 @@ impl RequestHandler for AdminCommandRequestHeader {
                 debug!("Prohibited MI admin command opcode: {:?}", self.op.id());
                 Err(ResponseStatus::InvalidCommandOpcode)
             }
-            _ => {
-                debug!("Unimplemented OPCODE: {:?}", self.op.id());
-                Err(ResponseStatus::InternalError)
-            }
         }
     }
 }
 
+fn admin_data_length(op: &AdminCommandRequestType) -> Option<u32> {
+    match op {
+        AdminCommandRequestType::GetLogPage(req) => Some(req.dlen),
+        AdminCommandRequestType::Identify(req) => Some(req.dlen),
+        AdminCommandRequestType::NamespaceAttachement(req) => Some(req.dlen),
+        AdminCommandRequestType::NamespaceManagement(req) => Some(req.dlen),
+        AdminCommandRequestType::FormatNvm(req) => Some(req.dlen),
+        AdminCommandRequestType::Sanitize(req) => Some(req.dlen),
+        AdminCommandRequestType::VirtualizationManagement(req) => Some(req.dlen),
+        AdminCommandRequestType::SetFeatures(req) => Some(req.dlen),
+        AdminCommandRequestType::GetFeatures(req) => Some(req.dlen),
+        AdminCommandRequestType::Abort(req) => Some(req.dlen),
+        AdminCommandRequestType::AsynchronousEventRequest(req) => Some(req.dlen),
+        AdminCommandRequestType::KeepAlive(req) => Some(req.dlen),
+        _ => None,
+    }
+}
+
+// Base v2.1, 5.1.13.2.2 and 5.1.13.2.9: The list CNS values report NSIDs greater
+// than the provided NSID, which must therefore be less than FFFFFFFEh
+fn admin_identify_list_nsid(nsid: u32) -> Result<u32, ResponseStatus> {
+    if nsid >= u32::MAX - 1 {
+        debug!("Invalid NSID for namespace list: {nsid:#x}");
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(8, 0),
+        ));
+    }
+
+    Ok(nsid)
+}
+
 fn admin_constrain_body(dofst: u32, dlen: u32, body: &[u8]) -> Result<&[u8], ResponseStatus> {
+    Ok(&body[admin_constrain_range(dofst, dlen, body.len())?])
+}
+
+fn admin_constrain_range(
+    dofst: u32,
+    dlen: u32,
+    len: usize,
+) -> Result<core::ops::Range<usize>, ResponseStatus> {
     // See Figure 136 in NVMe MI v2.0
 
     // Use send_response() instead
-    assert!(!body.is_empty());
+    assert!(len != 0);
 
-    // TODO: propagate PEL for all errors
     if dofst & 3 != 0 {
         debug!("Unnatural DOFST value: {dofst:?}");
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(28, 0),
+        ));
     }
 
     // FIXME: casts
     let dofst = dofst as usize;
     let dlen = dlen as usize;
 
-    if dofst >= body.len() {
+    if dofst >= len {
         debug!("DOFST value exceeds unconstrained response length: {dofst:?}");
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(28, 0),
+        ));
     }
 
     if dlen & 3 != 0 {
         debug!("Unnatural DLEN value: {dlen:?}");
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(32, 0),
+        ));
     }
 
-    if dlen > 4096 {
+    // Exceeding MDTS is handled as Invalid Field in Command by the admin
+    // dispatch. As MDTS is at least two pages, the MI bound always applies.
+    if dlen > crate::MAX_ADMIN_DATA_LEN as usize {
         debug!("DLEN too large: {dlen:?}");
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(32, 0),
+        ));
     }
 
-    if dlen > body.len() || body.len() - dlen < dofst {
+    if dlen > len || len - dlen < dofst {
         debug!(
             "Requested response data range beginning at {:?} for {:?} bytes exceeds bounds of unconstrained response length {:?}",
-            dofst,
-            dlen,
-            body.len()
+            dofst, dlen, len
         );
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(32, 0),
+        ));
     }
 
     if dlen == 0 {
         debug!("DLEN cleared for command with data response: {dlen:?}");
-        return Err(ResponseStatus::InvalidParameter);
+        return Err(ResponseStatus::InvalidParameter(
+            ParameterErrorLocation::new(32, 0),
+        ));
     }
 
-    let end = dofst + dlen;
-    Ok(&body[dofst..end])
+    Ok(dofst..dofst + dlen)
 }
 
-async fn admin_send_response_body<C>(resp: &mut C, body: &[u8]) -> Result<(), ResponseStatus>
+// Encodes only the DOFST / DLEN window of a response body
+trait EncodeBody<const S: usize>: Encode<S> {
+    fn encode_body<'b>(
+        &self,
+        dofst: u32,
+        dlen: u32,
+        buf: &'b mut [u8],
+    ) -> Result<Result<&'b [u8], AdminIoCqeGenericCommandStatus>, ResponseStatus> {
+        let range = admin_constrain_range(dofst, dlen, S)?;
+        let Some(buf) = buf.get_mut(..range.len()) else {
+            debug!(
+                "Scratch space too small for response window of {}",
+                range.len()
+            );
+            return Err(ResponseStatus::InternalError);
+        };
+        Ok(self
+            .encode_window(range.start, buf)
+            .map_err(AdminIoCqeGenericCommandStatus::from))
+    }
+}
+
+impl<const S: usize, T: Encode<S>> EncodeBody<S> for T {}
+
+async fn admin_send_response_body<C>(
+    resp: &mut C,
+    mic: crate::MessageIntegrityCheck,
+    csi: bool,
+    body: &[u8],
+) -> Result<(), ResponseStatus>
 where
     C: AsyncRespChannel,
 {
-    let mh = MessageHeader::respond(MessageType::NvmeAdminCommand).encode()?;
+    let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, csi).encode()?;
 
     let acrh = AdminCommandResponseHeader {
         status: ResponseStatus::Success,
@@ -944,19 +1399,19 @@ where
     }
     .encode()?;
 
-    send_response(resp, &[&mh.0, &acrh.0, body]).await;
-
-    Ok(())
+    send_response(resp, mic, &[&mh.0, &acrh.0, body]).await
 }
 
 async fn admin_send_status<C>(
     resp: &mut C,
+    mic: crate::MessageIntegrityCheck,
+    csi: bool,
     status: AdminIoCqeStatusType,
 ) -> Result<(), ResponseStatus>
 where
     C: AsyncRespChannel,
 {
-    let mh = MessageHeader::respond(MessageType::NvmeAdminCommand).encode()?;
+    let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, csi).encode()?;
 
     let acrh = AdminCommandResponseHeader {
         status: ResponseStatus::Success,
@@ -974,19 +1429,17 @@ where
     }
     .encode()?;
 
-    send_response(resp, &[&mh.0, &acrh.0]).await;
-
-    Ok(())
+    send_response(resp, mic, &[&mh.0, &acrh.0]).await
 }
 
 impl RequestHandler for AdminGetLogPageRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1000,16 +1453,35 @@ impl RequestHandler for AdminGetLogPageRequest {
             return Err(ResponseStatus::InvalidCommandSize);
         }
 
+        let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
         // Base v2.1, 5.1.12, Figure 202
         match &self.req {
             AdminGetLogPageLidRequestType::SupportedLogPages
+            | AdminGetLogPageLidRequestType::CommandsSupportedAndEffects
             | AdminGetLogPageLidRequestType::FeatureIdentifiersSupportedAndEffects => {
-                if self.csi != 0 {
-                    debug!("Support CSI");
+                let supported = crate::nvme::CommandSetIdentifier::try_from(self.csi)
+                    .map(crate::nvme::IoCommandSetFlags::from)
+                    .is_ok_and(|iocs| ctlr.iocs_supported.contains(iocs));
+                if !supported {
+                    debug!("Unsupported CSI {} for LID {:?}", self.csi, self.req);
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
-                            AdminIoCqeGenericCommandStatus::InternalError,
+                            AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                         ),
                     )
                     .await;
@@ -1017,20 +1489,12 @@ impl RequestHandler for AdminGetLogPageRequest {
             }
             AdminGetLogPageLidRequestType::ErrorInformation
             | AdminGetLogPageLidRequestType::SmartHealthInformation
+            | AdminGetLogPageLidRequestType::FirmwareSlotInformation
+            | AdminGetLogPageLidRequestType::ChangedNamespaceList
+            | AdminGetLogPageLidRequestType::DeviceSelfTest
             | AdminGetLogPageLidRequestType::SanitizeStatus => (),
         };
 
-        let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) else {
-            debug!("Unrecognised CTLID: {}", ctx.ctlid);
-            return admin_send_status(
-                resp,
-                AdminIoCqeStatusType::GenericCommandStatus(
-                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
-                ),
-            )
-            .await;
-        };
-
         let Some(flags) = ctlr.lsaes.get(self.req.id() as usize) else {
             debug!(
                 "LSAE mismatch with known LID {:?} on controller {}",
@@ -1038,6 +1502,8 @@ impl RequestHandler for AdminGetLogPageRequest {
             );
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -1053,6 +1519,8 @@ impl RequestHandler for AdminGetLogPageRequest {
             } else {
                 return admin_send_status(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     AdminIoCqeStatusType::GenericCommandStatus(
                         AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                     ),
@@ -1061,21 +1529,24 @@ impl RequestHandler for AdminGetLogPageRequest {
             }
         }
 
-        // Base v2.1, 5.1.12
-        let _numdw = if ctlr.lpa.contains(LogPageAttributes::Lpeds) {
-            todo!("Add support for extended NUMDL / NUMDU")
+        // Base v2.1, 5.1.12: NUMDU is only meaningful with extended data support
+        let numdw = if ctlr.lpa.contains(LogPageAttributes::Lpeds) {
+            self.numdw
         } else {
-            self.numdw & ((1u32 << 13) - 1)
+            self.numdw & ((1u32 << 12) - 1)
         };
+        let len = (numdw as u64 + 1) * 4;
 
         // TODO: RAE processing
 
         match &self.req {
             AdminGetLogPageLidRequestType::SupportedLogPages => {
-                if (self.numdw + 1) * 4 != 1024 {
+                if len != 1024 {
                     debug!("Implement support for NUMDL / NUMDU");
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1099,15 +1570,19 @@ impl RequestHandler for AdminGetLogPageRequest {
 
                 admin_send_response_body(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     admin_constrain_body(self.dofst, self.dlen, &slpr.0)?,
                 )
                 .await
             }
             AdminGetLogPageLidRequestType::ErrorInformation => {
-                if (self.numdw + 1) * 4 != 64 {
+                if len != 64 {
                     debug!("Implement support for NUMDL / NUMDU");
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1116,15 +1591,19 @@ impl RequestHandler for AdminGetLogPageRequest {
                 }
                 admin_send_response_body(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     admin_constrain_body(self.dofst, self.dlen, &[0u8; 64])?,
                 )
                 .await
             }
             AdminGetLogPageLidRequestType::SmartHealthInformation => {
-                if (self.numdw + 1) * 4 != 512 {
+                if len != 512 {
                     debug!("Implement support for NUMDL / NUMDU");
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1137,6 +1616,8 @@ impl RequestHandler for AdminGetLogPageRequest {
                 if lpol > 512 {
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                         ),
@@ -1144,56 +1625,85 @@ impl RequestHandler for AdminGetLogPageRequest {
                     .await;
                 }
 
-                if self.nsid != 0 && self.nsid != u32::MAX {
-                    if ctlr.lpa.contains(LogPageAttributes::Smarts) {
-                        todo!();
-                    } else {
+                // Base v2.1, 5.1.12.1.3: Namespace scope requires SMARTS, otherwise
+                // the counters aggregate all namespaces attached to the controller
+                let scope = if self.nsid != 0 && self.nsid != u32::MAX {
+                    if !ctlr.lpa.contains(LogPageAttributes::Smarts) {
                         return admin_send_status(
                             resp,
+                            mep.mic,
+                            mep.csi,
                             AdminIoCqeStatusType::GenericCommandStatus(
                                 AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                             ),
                         )
                         .await;
                     }
-                }
 
-                let shilpr = SmartHealthInformationLogPageResponse {
-                    cw: {
-                        let mut fs = FlagSet::empty();
+                    if !ctlr.active_ns.iter().any(|nsid| nsid.0 == self.nsid) {
+                        debug!("NSID {} is not active on controller", self.nsid);
+                        return admin_send_status(
+                            resp,
+                            mep.mic,
+                            mep.csi,
+                            AdminIoCqeStatusType::GenericCommandStatus(
+                                AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat,
+                            ),
+                        )
+                        .await;
+                    }
 
-                        if ctlr.spare < ctlr.spare_range.lower {
-                            fs |= crate::nvme::CriticalWarningFlags::Ascbt;
-                        }
+                    Some(NamespaceId(self.nsid))
+                } else {
+                    None
+                };
 
-                        if ctlr.temp < ctlr.temp_range.lower || ctlr.temp > ctlr.temp_range.upper {
-                            fs |= crate::nvme::CriticalWarningFlags::Ttc;
-                        }
+                let (rd_units, wr_units, hrc, hwc) = subsys
+                    .nss
+                    .iter()
+                    .filter(|ns| ctlr.active_ns.contains(&ns.id))
+                    .filter(|ns| scope.is_none_or(|nsid| nsid == ns.id))
+                    .fold((0u128, 0u128, 0u128, 0u128), |acc, ns| {
+                        (
+                            acc.0.saturating_add(ns.rd_units),
+                            acc.1.saturating_add(ns.wr_units),
+                            acc.2.saturating_add(ns.hrc),
+                            acc.3.saturating_add(ns.hwc),
+                        )
+                    });
+
+                let shilpr = SmartHealthInformationLogPageResponse {
+                    cw: {
+                        use crate::nvme::CriticalWarningFlags as T;
+                        use crate::nvme::mi::CriticalWarningFlags as F;
 
-                        // TODO: NDR
+                        let cwarn = ctlr.cwarn();
+                        let mut fs = FlagSet::empty();
 
-                        if ctlr.ro {
-                            fs |= crate::nvme::CriticalWarningFlags::Amro;
+                        for (f, t) in [
+                            (F::St, T::Ascbt),
+                            (F::Taut, T::Ttc),
+                            (F::Rd, T::Ndr),
+                            (F::Ro, T::Amro),
+                            (F::Vmbf, T::Vmbf),
+                            (F::Pmre, T::Pmrro),
+                        ] {
+                            if cwarn.contains(f) {
+                                fs |= t;
+                            }
                         }
 
-                        // TODO: VMBF
-                        // TODO: PMRRO
-
                         fs.into()
                     },
                     ctemp: ctlr.temp,
-                    avsp: <u8>::try_from(100 * ctlr.spare / ctlr.capacity)
-                        .map_err(|_| ResponseStatus::InternalError)?
-                        .clamp(0, 100),
-                    avspt: <u8>::try_from(100 * ctlr.spare_range.lower / ctlr.capacity)
-                        .map_err(|_| ResponseStatus::InternalError)?
-                        .clamp(0, 100),
+                    avsp: ctlr.spare_percent().ok_or(ResponseStatus::InternalError)?,
+                    avspt: ctlr.spare_range.lower,
                     pused: (100 * ctlr.write_age / ctlr.write_lifespan).clamp(0, 255) as u8,
                     egcws: FlagSet::empty().into(), // TODO: Endurance Groups
-                    dur: 0,
-                    duw: 0,
-                    hrc: 0,
-                    hwc: 0,
+                    dur: rd_units.div_ceil(1000),
+                    duw: wr_units.div_ceil(1000),
+                    hrc,
+                    hwc,
                     cbt: 0,
                     pwrc: 0, // TOOD: track power cycles
                     poh: 0,  // TODO: Track power on hours
@@ -1210,15 +1720,164 @@ impl RequestHandler for AdminGetLogPageRequest {
 
                 admin_send_response_body(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     admin_constrain_body(self.dofst, self.dlen, &shilpr.0)?,
                 )
                 .await
             }
+            AdminGetLogPageLidRequestType::FirmwareSlotInformation => {
+                if len != 512 {
+                    debug!("Implement support for NUMDL / NUMDU");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InternalError,
+                        ),
+                    )
+                    .await;
+                }
+
+                let mut frs: [WireString<8>; 7] = Default::default();
+                for (slot, fr) in frs.iter_mut().zip(ctlr.frs.iter()) {
+                    *slot = WireString::from(fr)?;
+                }
+
+                // TODO: Track the firmware slot to activate at the next reset
+                let fsilpr = FirmwareSlotInformationLogPageResponse {
+                    afi: ctlr.afi & 0x7,
+                    frs,
+                }
+                .encode()?;
+
+                admin_send_response_body(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    admin_constrain_body(self.dofst, self.dlen, &fsilpr.0)?,
+                )
+                .await
+            }
+            AdminGetLogPageLidRequestType::ChangedNamespaceList => {
+                if len != 4096 {
+                    debug!("Implement support for NUMDL / NUMDU");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InternalError,
+                        ),
+                    )
+                    .await;
+                }
+
+                let mut cnllpr = ChangedNamespaceListLogPageResponse::new();
+                if ctlr.chns.overflow {
+                    // Base v2.1, 5.1.12.1.5: More namespaces have changed than are tracked
+                    cnllpr.nsid.push(u32::MAX).map_err(|_| {
+                        debug!("Failed to push changed namespace list overflow sentinel");
+                        ResponseStatus::InternalError
+                    })?;
+                } else {
+                    for nsid in &ctlr.chns.nsids {
+                        cnllpr.nsid.push(nsid.0).map_err(|_| {
+                            debug!("Failed to push changed NSID {}", nsid.0);
+                            ResponseStatus::InternalError
+                        })?;
+                    }
+                }
+                let cnllpr = cnllpr.encode_into(&mut mep.scratch)?;
+                let body = admin_constrain_body(self.dofst, self.dlen, cnllpr.0)?;
+
+                // Base v2.1, 5.1.12, Figure 197, RAE
+                if self.lsp_rae & 0x80 == 0 {
+                    subsys.ctlrs[ctx.ctlid as usize].chns.clear();
+                }
+
+                admin_send_response_body(resp, mep.mic, mep.csi, body).await
+            }
+            AdminGetLogPageLidRequestType::CommandsSupportedAndEffects => {
+                if len != 4096 {
+                    debug!("Implement support for NUMDL / NUMDU");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InternalError,
+                        ),
+                    )
+                    .await;
+                }
+
+                let mut acs = [WireFlagSet::default(); 256];
+                for (e, f) in acs.iter_mut().zip(ctlr.acses) {
+                    *e = f.into();
+                }
+
+                let csaelpr = CommandsSupportedAndEffectsLogPageResponse { acs }
+                    .encode_into(&mut mep.scratch)?;
+
+                admin_send_response_body(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    admin_constrain_body(self.dofst, self.dlen, csaelpr.0)?,
+                )
+                .await
+            }
+            AdminGetLogPageLidRequestType::DeviceSelfTest => {
+                if len != 564 {
+                    debug!("Implement support for NUMDL / NUMDU");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InternalError,
+                        ),
+                    )
+                    .await;
+                }
+
+                let mut dstrd: [DeviceSelfTestResultDataStructure; 20] = Default::default();
+                for (rd, entry) in dstrd.iter_mut().zip(ctlr.dst.results.iter()) {
+                    *rd = DeviceSelfTestResultDataStructure {
+                        dsts: (entry.stc.id() << 4) | entry.result.id(),
+                        sn: entry.sn.unwrap_or(0),
+                        // Base v2.1, 5.1.12.1.6, Figure 210, VDI: NSIDV
+                        vdi: entry.nsid.is_some() as u8,
+                        poh: 0, // TODO: Track power on hours
+                        nsid: entry.nsid.map(|ns| ns.0).unwrap_or(0),
+                        ..Default::default()
+                    };
+                }
+
+                let dstlpr = DeviceSelfTestLogPageResponse {
+                    cdsto: ctlr.dst.current.map(|(stc, _)| stc.id()).unwrap_or(0),
+                    cdstc: ctlr.dst.completion,
+                    dstrd,
+                }
+                .encode()?;
+
+                admin_send_response_body(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    admin_constrain_body(self.dofst, self.dlen, &dstlpr.0)?,
+                )
+                .await
+            }
             AdminGetLogPageLidRequestType::FeatureIdentifiersSupportedAndEffects => {
-                if (self.numdw + 1) * 4 != 1024 {
+                if len != 1024 {
                     debug!("Implement support for NUMDL / NUMDU");
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1228,6 +1887,8 @@ impl RequestHandler for AdminGetLogPageRequest {
 
                 admin_send_response_body(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     admin_constrain_body(
                         self.dofst,
                         self.dlen,
@@ -1238,10 +1899,12 @@ impl RequestHandler for AdminGetLogPageRequest {
                 .await
             }
             AdminGetLogPageLidRequestType::SanitizeStatus => {
-                if (self.numdw + 1) * 4 != 512 {
+                if len != 512 {
                     debug!("Implement support for NUMDL / NUMDU");
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1250,7 +1913,7 @@ impl RequestHandler for AdminGetLogPageRequest {
                 }
 
                 let sslpr = SanitizeStatusLogPageResponse {
-                    sprog: u16::MAX,
+                    sprog: subsys.sprog,
                     sstat: subsys.sstat.into(),
                     scdw10: {
                         if let Some(sconf) = subsys.sconf {
@@ -1272,6 +1935,8 @@ impl RequestHandler for AdminGetLogPageRequest {
 
                 admin_send_response_body(
                     resp,
+                    mep.mic,
+                    mep.csi,
                     admin_constrain_body(self.dofst, self.dlen, &sslpr.0)?,
                 )
                 .await
@@ -1283,11 +1948,11 @@ impl RequestHandler for AdminGetLogPageRequest {
 impl RequestHandler for AdminIdentifyRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1309,48 +1974,43 @@ impl RequestHandler for AdminIdentifyRequest {
                         Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
                     }
                     NamespaceIdDisposition::Broadcast => {
-                        AdminIdentifyNvmIdentifyNamespaceResponse {
-                            lbaf0_lbads: 9, // TODO: Tie to controller model
-                            ..Default::default()
-                        }
-                        .encode()
-                        .map_err(AdminIoCqeGenericCommandStatus::from)
+                        AdminIdentifyNvmIdentifyNamespaceResponse::common().encode_body(
+                            self.dofst,
+                            self.dlen,
+                            &mut mep.scratch,
+                        )?
                     }
                     NamespaceIdDisposition::Unallocated => {
                         debug!("Unallocated NSID: {}", self.nsid);
                         Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
                     }
                     NamespaceIdDisposition::Inactive(_) => {
-                        AdminIdentifyNvmIdentifyNamespaceResponse::default()
-                            .encode()
-                            .map_err(AdminIoCqeGenericCommandStatus::from)
+                        AdminIdentifyNvmIdentifyNamespaceResponse::default().encode_body(
+                            self.dofst,
+                            self.dlen,
+                            &mut mep.scratch,
+                        )?
                     }
                     // 4.1.5.1 NVM Command Set Spec, v1.0c
-                    NamespaceIdDisposition::Active(ns) => {
-                        Into::<AdminIdentifyNvmIdentifyNamespaceResponse>::into(ns)
-                            .encode()
-                            .map_err(AdminIoCqeGenericCommandStatus::from)
-                    }
+                    NamespaceIdDisposition::Active(ns) => Into::<
+                        AdminIdentifyNvmIdentifyNamespaceResponse,
+                    >::into(ns)
+                    .encode_body(self.dofst, self.dlen, &mut mep.scratch)?,
                 }
             }
             AdminIdentifyCnsRequestType::IdentifyController => {
                 if let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) {
                     AdminIdentifyControllerResponse {
-                        vid: subsys.info.pci_vid,
-                        ssvid: subsys.info.pci_svid,
+                        vid: ctlr.pcie.vid(),
+                        ssvid: ctlr.pcie.svid(),
                         sn: WireString::from(subsys.sn)?,
                         mn: WireString::from(subsys.mn)?,
-                        fr: WireString::from(subsys.fr)?,
+                        fr: WireString::from(ctlr.firmware_revision())?,
                         rab: 0,
-                        ieee: {
-                            // 4.5.3, Base v2.1
-                            let mut fixup = subsys.info.ieee_oui;
-                            fixup.reverse();
-                            fixup
-                        },
+                        ieee: subsys.info.ieee_oui_wire(),
                         cmic: ((subsys.ctlrs.len() > 1) as u8) << 1 // MCTRS
                         | ((subsys.ports.len() > 1) as u8), // MPORTS
-                        mdts: 0,
+                        mdts: ctlr.mdts,
                         cntlid: ctlr.id.0,
                         ver: 0,
                         rtd3r: 0,
@@ -1368,16 +2028,17 @@ impl RequestHandler for AdminIdentifyRequest {
                         vwci: 0,
                         mec: ((subsys.ports.iter().any(|p| matches!(p.typ, crate::PortType::Pcie(_)))) as u8) << 1 // PCIEME
                         | (subsys.ports.iter().any(|p| matches!(p.typ, crate::PortType::TwoWire(_)))) as u8, // TWPME
-                        ocas: 0,
-                        acl: 0,
-                        aerl: 0,
-                        frmw: 0,
+                        oacs: ctlr.oacs.into(),
+                        acl: ctlr.acl,
+                        aerl: ctlr.aerl,
+                        // Base v2.1, 5.1.13.2.1, Figure 313, FRMW: NOFS
+                        frmw: (ctlr.frs.len() as u8) << 1,
                         lpa: ctlr.lpa.into(),
                         elpe: 0,
                         npss: 0,
                         avscc: 0,
-                        wctemp: 0x157,
-                        cctemp: 0x157,
+                        wctemp: ctlr.wctemp,
+                        cctemp: ctlr.cctemp,
                         fwug: 0,
                         kas: 0,
                         cqt: 0,
@@ -1393,16 +2054,15 @@ impl RequestHandler for AdminIdentifyRequest {
                         awupf: 0,
                         icsvscc: 0,
                         nwpc: 0,
-                        mnan: 0,
-                        subnqn: WireString::new(),
+                        mnan: NamespaceId::capacity(subsys),
+                        subnqn: WireString::from(&subsys.subnqn())?,
                         fcatt: 0,
                         msdbd: 0,
                         ofcs: 0,
                         apsta: 0,
                         sanicap: subsys.sanicap.into(),
                     }
-                    .encode()
-                    .map_err(AdminIoCqeGenericCommandStatus::from)
+                    .encode_body(self.dofst, self.dlen, &mut mep.scratch)?
                 } else {
                     debug!("No such CTLID: {}", ctx.ctlid);
                     Err(AdminIoCqeGenericCommandStatus::InvalidFieldInCommand)
@@ -1410,45 +2070,35 @@ impl RequestHandler for AdminIdentifyRequest {
             }
             AdminIdentifyCnsRequestType::ActiveNamespaceIDList => {
                 // 5.1.13.2.2, Base v2.1
-                let mut active: heapless::Vec<u32, MAX_NAMESPACES> = subsys
+                let start = admin_identify_list_nsid(self.nsid)?;
+                let mut active: heapless::Vec<u32, NSS> = subsys
                     .ctlrs
                     .iter()
                     .flat_map(|c| c.active_ns.iter())
                     .map(|nsid| nsid.0)
-                    .filter(|nsid| *nsid > self.nsid)
+                    .filter(|nsid| *nsid > start)
                     .collect();
                 active.sort_unstable();
-                // IndexSet iterator is insertion-order:
-                // https://docs.rs/heapless/0.8.0/heapless/struct.IndexSet.html#method.iter
-                let unique: heapless::FnvIndexSet<u32, MAX_NAMESPACES> =
-                    active.iter().copied().collect();
 
                 let mut aianidlr = AdminIdentifyActiveNamespaceIdListResponse::new();
                 // TODO: Improve this with better iterator handling?
-                for nsid in unique.iter() {
+                for (i, nsid) in active.iter().enumerate() {
+                    // Namespaces shared between controllers are adjacent once sorted
+                    if i > 0 && active[i - 1] == *nsid {
+                        continue;
+                    }
+
                     if aianidlr.nsid.push(*nsid).is_err() {
                         debug!("Failed to insert NSID {nsid}");
                         return Err(ResponseStatus::InternalError);
                     };
                 }
-                aianidlr
-                    .encode()
-                    .map_err(AdminIoCqeGenericCommandStatus::from)
+                aianidlr.encode_body(self.dofst, self.dlen, &mut mep.scratch)?
             }
             AdminIdentifyCnsRequestType::NamespaceIdentificationDescriptorList => {
                 // 5.1.13.2.3, Base v2.1
                 match NamespaceId(self.nsid).disposition(subsys) {
-                    NamespaceIdDisposition::Invalid => {
-                        if self.nsid == u32::MAX - 1 {
-                            debug!(
-                                "Unacceptable NSID for Namespace Identification Descriptor List"
-                            );
-                        } else {
-                            debug!("Invalid NSID: {}", self.nsid);
-                        }
-                        Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
-                    }
-                    NamespaceIdDisposition::Broadcast => {
+                    NamespaceIdDisposition::Invalid | NamespaceIdDisposition::Broadcast => {
                         debug!("Invalid NSID: {}", self.nsid);
                         Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
                     }
@@ -1472,26 +2122,28 @@ impl RequestHandler for AdminIdentifyRequest {
                                 vec
                             },
                         }
-                        .encode()
-                        .map_err(AdminIoCqeGenericCommandStatus::from)
+                        .encode_body(
+                            self.dofst,
+                            self.dlen,
+                            &mut mep.scratch,
+                        )?
                     }
                 }
             }
             AdminIdentifyCnsRequestType::AllocatedNamespaceIdList => {
                 // 5.1.13.2.9, Base v2.1
-                if self.nsid >= u32::MAX - 1 {
-                    debug!("Invalid NSID");
-                    return Err(ResponseStatus::InvalidParameter);
-                }
+                let start = admin_identify_list_nsid(self.nsid)?;
 
-                assert!(NamespaceId::max(subsys) < (4096 / core::mem::size_of::<u32>()) as u32);
+                assert!(
+                    NamespaceId::capacity(subsys) < (4096 / core::mem::size_of::<u32>()) as u32
+                );
                 AdminIdentifyAllocatedNamespaceIdListResponse {
                     nsid: {
-                        let mut allocated: heapless::Vec<u32, MAX_NAMESPACES> = subsys
+                        let mut allocated: heapless::Vec<u32, NSS> = subsys
                             .nss
                             .iter()
                             .map(|ns| ns.id.0)
-                            .filter(|nsid| *nsid > self.nsid)
+                            .filter(|nsid| *nsid > start)
                             .collect();
                         allocated.sort_unstable();
                         let mut vec = WireVec::new();
@@ -1504,40 +2156,68 @@ impl RequestHandler for AdminIdentifyRequest {
                         vec
                     },
                 }
-                .encode()
-                .map_err(AdminIoCqeGenericCommandStatus::from)
+                .encode_body(self.dofst, self.dlen, &mut mep.scratch)?
+            }
+            AdminIdentifyCnsRequestType::IdentifyNamespace => {
+                // Base v2.1, 5.1.13.2.8
+                match NamespaceId(self.nsid).disposition(subsys) {
+                    NamespaceIdDisposition::Invalid | NamespaceIdDisposition::Unallocated => {
+                        debug!("Invalid NSID: {}", self.nsid);
+                        Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
+                    }
+                    NamespaceIdDisposition::Broadcast => subsys
+                        .nss
+                        .iter()
+                        .map(AdminIdentifyIoCommandSetIndependentNamespaceResponse::from)
+                        .reduce(AdminIdentifyIoCommandSetIndependentNamespaceResponse::merge)
+                        .unwrap_or_default()
+                        .encode_body(self.dofst, self.dlen, &mut mep.scratch)?,
+                    NamespaceIdDisposition::Inactive(_) => {
+                        AdminIdentifyIoCommandSetIndependentNamespaceResponse::default()
+                            .encode_body(self.dofst, self.dlen, &mut mep.scratch)?
+                    }
+                    NamespaceIdDisposition::Active(ns) => {
+                        AdminIdentifyIoCommandSetIndependentNamespaceResponse::from(ns)
+                            .encode_body(self.dofst, self.dlen, &mut mep.scratch)?
+                    }
+                }
             }
             AdminIdentifyCnsRequestType::IdentifyNamespaceForAllocatedNamespaceId => {
                 // Base v2.1, 5.1.13.2.10
                 match NamespaceId(self.nsid).disposition(subsys) {
                     NamespaceIdDisposition::Invalid | NamespaceIdDisposition::Broadcast => {
+                        debug!("Invalid NSID: {}", self.nsid);
                         Err(AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat)
                     }
                     NamespaceIdDisposition::Unallocated => {
-                        AdminIdentifyNvmIdentifyNamespaceResponse::default()
-                            .encode()
-                            .map_err(AdminIoCqeGenericCommandStatus::from)
+                        AdminIdentifyNvmIdentifyNamespaceResponse::default().encode_body(
+                            self.dofst,
+                            self.dlen,
+                            &mut mep.scratch,
+                        )?
                     }
                     NamespaceIdDisposition::Inactive(ns) | NamespaceIdDisposition::Active(ns) => {
                         let ainvminr: AdminIdentifyNvmIdentifyNamespaceResponse = ns.into();
-                        ainvminr
-                            .encode()
-                            .map_err(AdminIoCqeGenericCommandStatus::from)
+                        ainvminr.encode_body(self.dofst, self.dlen, &mut mep.scratch)?
                     }
                 }
             }
             AdminIdentifyCnsRequestType::NamespaceAttachedControllerList => {
                 match NamespaceId(self.nsid).disposition(subsys) {
-                    NamespaceIdDisposition::Invalid => ControllerListResponse::new()
-                        .encode()
-                        .map_err(AdminIoCqeGenericCommandStatus::from),
+                    NamespaceIdDisposition::Invalid => ControllerListResponse::new().encode_body(
+                        self.dofst,
+                        self.dlen,
+                        &mut mep.scratch,
+                    )?,
                     NamespaceIdDisposition::Broadcast => {
                         Err(AdminIoCqeGenericCommandStatus::InvalidFieldInCommand)
                     }
                     NamespaceIdDisposition::Unallocated | NamespaceIdDisposition::Inactive(_) => {
-                        ControllerListResponse::new()
-                            .encode()
-                            .map_err(AdminIoCqeGenericCommandStatus::from)
+                        ControllerListResponse::new().encode_body(
+                            self.dofst,
+                            self.dlen,
+                            &mut mep.scratch,
+                        )?
                     }
                     NamespaceIdDisposition::Active(ns) => {
                         let mut clr = ControllerListResponse::new();
@@ -1554,7 +2234,7 @@ impl RequestHandler for AdminIdentifyRequest {
                             }
                         }
                         clr.update()?;
-                        clr.encode().map_err(AdminIoCqeGenericCommandStatus::from)
+                        clr.encode_body(self.dofst, self.dlen, &mut mep.scratch)?
                     }
                 }
             }
@@ -1572,19 +2252,60 @@ impl RequestHandler for AdminIdentifyRequest {
                     };
                 }
                 cl.update()?;
-                cl.encode().map_err(AdminIoCqeGenericCommandStatus::from)
+                cl.encode_body(self.dofst, self.dlen, &mut mep.scratch)?
             }
-            AdminIdentifyCnsRequestType::SecondaryControllerList => {
+            AdminIdentifyCnsRequestType::PrimaryControllerCapabilities => {
                 let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) else {
                     debug!("No such CTLID: {}", ctx.ctlid);
-                    return Err(ResponseStatus::InvalidParameter);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(6, 0),
+                    ));
                 };
 
-                if !ctlr.secondaries.is_empty() {
-                    todo!("Support listing secondary controllers");
+                AdminIdentifyPrimaryControllerCapabilitiesResponse {
+                    cntlid: ctlr.id.0,
+                    portid: ctlr.port.0.into(),
+                    crt: (ControllerResourceTypes::Vq | ControllerResourceTypes::Vi).into(),
+                    vqfrt: ctlr.virt.vq.frt,
+                    vqrfa: ctlr.secondaries.iter().map(|sc| sc.nvq as u32).sum(),
+                    vqrfap: ctlr.virt.vq.rfap,
+                    vqprt: ctlr.virt.vq.prt,
+                    vqfrsm: ctlr.virt.vq.frsm,
+                    vqgran: ctlr.virt.vq.gran,
+                    vifrt: ctlr.virt.vi.frt,
+                    virfa: ctlr.secondaries.iter().map(|sc| sc.nvi as u32).sum(),
+                    virfap: ctlr.virt.vi.rfap,
+                    viprt: ctlr.virt.vi.prt,
+                    vifrsm: ctlr.virt.vi.frsm,
+                    vigran: ctlr.virt.vi.gran,
                 }
+                .encode_body(self.dofst, self.dlen, &mut mep.scratch)?
+            }
+            AdminIdentifyCnsRequestType::SecondaryControllerList => {
+                let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) else {
+                    debug!("No such CTLID: {}", ctx.ctlid);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(6, 0),
+                    ));
+                };
 
-                Ok(([0u8; 4096], 4096usize))
+                let mut scl = SecondaryControllerListResponse::new();
+                for sc in ctlr.secondaries.iter().filter(|v| v.id.0 >= self.cntid) {
+                    let entry = SecondaryControllerEntry {
+                        scid: sc.id.0,
+                        pcid: ctlr.id.0,
+                        scs: sc.online as u8,
+                        vfn: sc.vfn,
+                        nvq: sc.nvq,
+                        nvi: sc.nvi,
+                    };
+                    if scl.entries.push(entry).is_err() {
+                        debug!("Failed to push secondary controller {}", sc.id.0);
+                        return Err(ResponseStatus::InternalError);
+                    }
+                }
+                scl.update()?;
+                scl.encode_body(self.dofst, self.dlen, &mut mep.scratch)?
             }
             _ => {
                 debug!("Unimplemented CNS: {self:?}");
@@ -1593,16 +2314,16 @@ impl RequestHandler for AdminIdentifyRequest {
         };
 
         match res {
-            Ok(response) => {
-                admin_send_response_body(
+            Ok(response) => admin_send_response_body(resp, mep.mic, mep.csi, response).await,
+            Err(err) => {
+                admin_send_status(
                     resp,
-                    admin_constrain_body(self.dofst, self.dlen, &response.0)?,
+                    mep.mic,
+                    mep.csi,
+                    AdminIoCqeStatusType::GenericCommandStatus(err),
                 )
                 .await
             }
-            Err(err) => {
-                admin_send_status(resp, AdminIoCqeStatusType::GenericCommandStatus(err)).await
-            }
         }
     }
 }
@@ -1610,11 +2331,11 @@ impl RequestHandler for AdminIdentifyRequest {
 impl RequestHandler for AdminNamespaceManagementRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1640,6 +2361,8 @@ impl RequestHandler for AdminNamespaceManagementRequest {
                     debug!("Support CSI {}", self.csi);
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
@@ -1652,13 +2375,15 @@ impl RequestHandler for AdminNamespaceManagementRequest {
                     // TODO: Implement Base v2.1, 5.1.21.1, Figure 370
                     return admin_send_status(
                         resp,
+                        mep.mic,
+                        mep.csi,
                         AdminIoCqeStatusType::GenericCommandStatus(
                             AdminIoCqeGenericCommandStatus::InternalError,
                         ),
                     )
                     .await;
                 };
-                let mh = MessageHeader::respond(MessageType::NvmeAdminCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
 
                 let acrh = AdminCommandResponseHeader {
                     status: ResponseStatus::Success,
@@ -1678,9 +2403,7 @@ impl RequestHandler for AdminNamespaceManagementRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &acrh.0]).await;
-
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
             }
             crate::nvme::mi::AdminNamespaceManagementSelect::Delete => {
                 let res = subsys.remove_namespace(NamespaceId(self.nsid));
@@ -1695,7 +2418,7 @@ impl RequestHandler for AdminNamespaceManagementRequest {
                         )
                     }
                 };
-                let mh = MessageHeader::respond(MessageType::NvmeAdminCommand).encode()?;
+                let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
 
                 let acrh = AdminCommandResponseHeader {
                     status: ResponseStatus::Success,
@@ -1713,9 +2436,7 @@ impl RequestHandler for AdminNamespaceManagementRequest {
                 }
                 .encode()?;
 
-                send_response(resp, &[&mh.0, &acrh.0]).await;
-
-                Ok(())
+                send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
             }
         }
     }
@@ -1724,11 +2445,11 @@ impl RequestHandler for AdminNamespaceManagementRequest {
 impl RequestHandler for AdminNamespaceAttachmentRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1741,20 +2462,23 @@ impl RequestHandler for AdminNamespaceAttachmentRequest {
         #[repr(u8)]
         enum CommandSpecificStatus {
             NamespaceAlreadyAttached = 0x18,
+            NamespaceIsPrivate = 0x19,
             NamespaceNotAttached = 0x1a,
             ControllerListInvalid = 0x1c,
             NamespaceAttachmentLimitExceeded = 0x27,
+            IoCommandSetNotSupported = 0x29,
+            IoCommandSetNotEnabled = 0x2a,
         }
         unsafe impl Discriminant<u8> for CommandSpecificStatus {}
 
-        impl From<ControllerError> for CommandSpecificStatus {
-            fn from(value: ControllerError) -> Self {
+        impl From<NamespaceAttachmentError> for CommandSpecificStatus {
+            fn from(value: NamespaceAttachmentError) -> Self {
                 match value {
-                    ControllerError::NamespaceAlreadyAttached => Self::NamespaceAlreadyAttached,
-                    ControllerError::NamespaceAttachmentLimitExceeded => {
+                    NamespaceAttachmentError::AlreadyAttached => Self::NamespaceAlreadyAttached,
+                    NamespaceAttachmentError::LimitExceeded => {
                         Self::NamespaceAttachmentLimitExceeded
                     }
-                    ControllerError::NamespaceNotAttached => Self::NamespaceNotAttached,
+                    NamespaceAttachmentError::NotAttached => Self::NamespaceNotAttached,
                 }
             }
         }
@@ -1771,33 +2495,46 @@ impl RequestHandler for AdminNamespaceAttachmentRequest {
             return Err(ResponseStatus::InvalidCommandSize);
         }
 
+        // Base v2.1, 5.1.20: The broadcast NSID does not identify a namespace
+        // to attach or detach
         if self.nsid == u32::MAX {
             debug!("Refusing to perform {:?} for broadcast NSID", self.sel);
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
-                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                    AdminIoCqeGenericCommandStatus::InvalidNamespaceOrFormat,
                 ),
             )
             .await;
         }
 
-        // TODO: Handle MAXCNA
-
         let mut status = AdminIoCqeStatusType::GenericCommandStatus(
             AdminIoCqeGenericCommandStatus::SuccessfulCompletion,
         );
 
         let action = match &self.sel {
             crate::nvme::AdminNamespaceAttachmentSelect::ControllerAttach => {
-                |ctlr: &mut Controller, ns: NamespaceId| ctlr.attach_namespace(ns)
+                |ctlr: &mut GenericController<NSS>, ns: NamespaceId| ctlr.attach_namespace(ns)
             }
             crate::nvme::AdminNamespaceAttachmentSelect::ControllerDetach => {
-                |ctlr: &mut Controller, ns: NamespaceId| ctlr.detach_namespace(ns)
+                |ctlr: &mut GenericController<NSS>, ns: NamespaceId| ctlr.detach_namespace(ns)
             }
         };
 
+        let ns = subsys.nss.iter().find(|ns| ns.id.0 == self.nsid);
+        let maxcna = ns.map_or(u16::MAX, |ns| ns.maxcna);
+        let private = ns.is_some_and(|ns| ns.private);
+        let csi = ns.map(|ns| crate::nvme::IoCommandSetFlags::from(ns.csi()));
+
         for cid in &self.body.ids.0 {
+            let attached = subsys
+                .ctlrs
+                .iter()
+                .filter(|c| c.active_ns.iter().any(|ns| ns.0 == self.nsid))
+                .count();
+
             let Some(ctlr) = subsys.ctlrs.get_mut(*cid as usize) else {
                 debug!("Unrecognised controller ID: {cid}");
                 status = AdminIoCqeStatusType::CommandSpecificStatus(
@@ -1806,22 +2543,71 @@ impl RequestHandler for AdminNamespaceAttachmentRequest {
                 break;
             };
 
-            // TODO: Allow addition of non-IO controllers
-            if ctlr.cntrltype != ControllerType::Io {
-                debug!(
-                    "Require {:?} controller type, have {:?}",
-                    ControllerType::Io,
-                    ctlr.cntrltype
-                );
-                status = AdminIoCqeStatusType::CommandSpecificStatus(
-                    CommandSpecificStatus::ControllerListInvalid.id(),
-                );
-                break;
+            match ctlr.cntrltype {
+                ControllerType::Io => (),
+                // Base v2.1, 3.1.3.3: Administrative controllers support no I/O
+                // Command Set, so have no namespaces to attach
+                ControllerType::Administrative
+                    if self.sel
+                        == crate::nvme::AdminNamespaceAttachmentSelect::ControllerAttach =>
+                {
+                    debug!("CTLRID {cid} is an administrative controller");
+                    status = AdminIoCqeStatusType::CommandSpecificStatus(
+                        CommandSpecificStatus::IoCommandSetNotSupported.id(),
+                    );
+                    break;
+                }
+                ControllerType::Administrative => (),
+                ControllerType::Discovery => {
+                    debug!(
+                        "Require {:?} controller type, have {:?}",
+                        ControllerType::Io,
+                        ctlr.cntrltype
+                    );
+                    status = AdminIoCqeStatusType::CommandSpecificStatus(
+                        CommandSpecificStatus::ControllerListInvalid.id(),
+                    );
+                    break;
+                }
             }
 
-            // TODO: Handle Namespace Is Private
-            // TODO: Handle I/O Command Set Not Supported
-            // TODO: Handle I/O Command Set Not Enabled
+            if self.sel == crate::nvme::AdminNamespaceAttachmentSelect::ControllerAttach
+                && !ctlr.active_ns.iter().any(|ns| ns.0 == self.nsid)
+            {
+                if private && attached > 0 {
+                    debug!("NSID {} is private and already attached", self.nsid);
+                    status = AdminIoCqeStatusType::CommandSpecificStatus(
+                        CommandSpecificStatus::NamespaceIsPrivate.id(),
+                    );
+                    break;
+                }
+
+                if attached >= maxcna.into() {
+                    debug!("NSID {} is attached to {attached} controllers", self.nsid);
+                    status = AdminIoCqeStatusType::CommandSpecificStatus(
+                        CommandSpecificStatus::NamespaceAttachmentLimitExceeded.id(),
+                    );
+                    break;
+                }
+
+                if let Some(csi) = csi {
+                    if !ctlr.iocs_supported.contains(csi) {
+                        debug!("CTLRID {cid} does not support {csi:?}");
+                        status = AdminIoCqeStatusType::CommandSpecificStatus(
+                            CommandSpecificStatus::IoCommandSetNotSupported.id(),
+                        );
+                        break;
+                    }
+
+                    if !ctlr.iocs_enabled.contains(csi) {
+                        debug!("CTLRID {cid} has not enabled {csi:?}");
+                        status = AdminIoCqeStatusType::CommandSpecificStatus(
+                            CommandSpecificStatus::IoCommandSetNotEnabled.id(),
+                        );
+                        break;
+                    }
+                }
+            }
 
             // XXX: Should this be transactional? Two loops?
             if let Err(err) = action(ctlr, NamespaceId(self.nsid)) {
@@ -1831,7 +2617,7 @@ impl RequestHandler for AdminNamespaceAttachmentRequest {
             }
         }
 
-        let mh = MessageHeader::respond(MessageType::NvmeAdminCommand).encode()?;
+        let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
 
         let acrh = AdminCommandResponseHeader {
             status: ResponseStatus::Success,
@@ -1853,20 +2639,18 @@ impl RequestHandler for AdminNamespaceAttachmentRequest {
         }
         .encode()?;
 
-        send_response(resp, &[&mh.0, &acrh.0]).await;
-
-        Ok(())
+        send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
     }
 }
 
 impl RequestHandler for AdminSanitizeRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         _ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1884,6 +2668,8 @@ impl RequestHandler for AdminSanitizeRequest {
             debug!("Invalid sanitize configuration: {}", self.config);
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -1895,6 +2681,8 @@ impl RequestHandler for AdminSanitizeRequest {
             debug!("Request for No-Deallocate After Sanitize when No-Deallocate is inhibited");
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -1902,14 +2690,57 @@ impl RequestHandler for AdminSanitizeRequest {
             .await;
         }
 
+        if subsys.sstat.in_progress() {
+            debug!("Request for sanitize while sanitize is in progress");
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::SanitizeInProgress,
+                ),
+            )
+            .await;
+        }
+
         // TODO: Implement action latency, progress state machine, error states
         match config.sanact {
-            SanitizeAction::Reserved => Err(ResponseStatus::InvalidParameter),
+            SanitizeAction::Reserved => Err(ResponseStatus::InvalidParameter(
+                ParameterErrorLocation::new(44, 0),
+            )),
             SanitizeAction::ExitFailureMode | SanitizeAction::ExitMediaVerificationState => {
-                if subsys.ssi.sans != SanitizeState::Idle {
-                    todo!("Implement sanitize state machine!");
+                match (config.sanact, subsys.ssi.sans) {
+                    (_, SanitizeState::Idle) => (),
+                    // Post-verification deallocation completes immediately
+                    (SanitizeAction::ExitFailureMode, SanitizeState::UnrestrictedFailure)
+                    | (
+                        SanitizeAction::ExitMediaVerificationState,
+                        SanitizeState::MediaVerification,
+                    ) => {
+                        subsys.ssi = SanitizeStateInformation {
+                            sans: SanitizeState::Idle,
+                            fails: 0,
+                        };
+                    }
+                    // The action doesn't exit the current state. Only a successful
+                    // sanitize operation exits the restricted failure state.
+                    (_, sans) => {
+                        debug!(
+                            "Invalid exit action {:?} in state {:?}",
+                            config.sanact, sans
+                        );
+                        return admin_send_status(
+                            resp,
+                            mep.mic,
+                            mep.csi,
+                            AdminIoCqeStatusType::GenericCommandStatus(
+                                AdminIoCqeGenericCommandStatus::CommandSequenceError,
+                            ),
+                        )
+                        .await;
+                    }
                 }
-                admin_send_response_body(resp, &[]).await
+                admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
             }
             SanitizeAction::StartBlockErase | SanitizeAction::StartCryptoErase => {
                 subsys.ssi = SanitizeStateInformation {
@@ -1924,7 +2755,7 @@ impl RequestHandler for AdminSanitizeRequest {
                 };
                 subsys.sconf = Some(self.config.try_into()?);
 
-                admin_send_response_body(resp, &[]).await
+                admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
             }
             SanitizeAction::StartOverwrite => {
                 subsys.ssi = SanitizeStateInformation {
@@ -1939,7 +2770,7 @@ impl RequestHandler for AdminSanitizeRequest {
                 };
                 subsys.sconf = Some(self.config.try_into()?);
 
-                admin_send_response_body(resp, &[]).await
+                admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
             }
         }
     }
@@ -1948,11 +2779,11 @@ impl RequestHandler for AdminSanitizeRequest {
 impl RequestHandler for AdminFormatNvmRequest {
     type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
         _app: A,
@@ -1970,6 +2801,8 @@ impl RequestHandler for AdminFormatNvmRequest {
             debug!("Unrecognised CTLID: {}", ctx.ctlid);
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -1981,6 +2814,8 @@ impl RequestHandler for AdminFormatNvmRequest {
             debug!("Invalid configuration for Admin Format NVM");
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -1988,21 +2823,28 @@ impl RequestHandler for AdminFormatNvmRequest {
             .await;
         };
 
-        if config.lbafi != 0 {
+        let Some(block_order) = crate::MIN_BLOCK_ORDER
+            .checked_add(config.lbafi)
+            .filter(|bo| *bo <= crate::MAX_BLOCK_ORDER)
+        else {
             debug!("Unsupported LBA format index: {}", config.lbafi);
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
             )
             .await;
-        }
+        };
 
         if !ctlr.active_ns.iter().any(|ns| ns.0 == self.nsid) && self.nsid != u32::MAX {
             debug!("Unrecognised NSID: {}", self.nsid);
             return admin_send_status(
                 resp,
+                mep.mic,
+                mep.csi,
                 AdminIoCqeStatusType::GenericCommandStatus(
                     AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
                 ),
@@ -2010,81 +2852,801 @@ impl RequestHandler for AdminFormatNvmRequest {
             .await;
         }
 
+        // Base v2.1, 5.1.10: With FNA.FNS set all namespaces are formatted
+        // together, otherwise the broadcast NSID selects the attached namespaces
+        let fns = ctlr.fna.contains(crate::nvme::FormatNvmAttributes::Fns);
+        for ns in subsys.nss.iter_mut().filter(|ns| {
+            fns || ns.id.0 == self.nsid
+                || (self.nsid == u32::MAX && ctlr.active_ns.contains(&ns.id))
+        }) {
+            // The block order was validated above
+            let _ = ns.set_lba_format(block_order);
+        }
+
         // TODO: handle config.ses
 
-        admin_send_response_body(resp, &[]).await
+        admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
     }
 }
 
-impl RequestHandler for PcieCommandRequestHeader {
-    type Ctx = PcieCommandRequestHeader;
+impl RequestHandler for AdminVirtualizationManagementRequest {
+    type Ctx = AdminCommandRequestHeader;
 
-    async fn handle<A, C>(
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
         &self,
         ctx: &Self::Ctx,
-        _mep: &mut crate::ManagementEndpoint,
-        subsys: &mut crate::Subsystem,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         rest: &[u8],
         resp: &mut C,
-        _app: A,
+        mut app: A,
     ) -> Result<(), ResponseStatus>
     where
-        A: AsyncFnMut(crate::CommandEffect) -> Result<(), CommandEffectError>,
-        C: mctp::AsyncRespChannel,
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
     {
-        match &ctx.op {
-            super::PcieCommandRequestType::ConfigurationRead(req) => {
-                if !rest.is_empty() {
-                    debug!("Invalid request size for PcieCommand");
-                    return Err(ResponseStatus::InvalidCommandSize);
-                }
+        // Base v2.1, Virtualization Management, Command Specific Status Values
+        #[expect(clippy::enum_variant_names)]
+        #[repr(u8)]
+        enum CommandSpecificStatus {
+            InvalidControllerIdentifier = 0x1f,
+            InvalidSecondaryControllerState = 0x20,
+            InvalidNumberOfControllerResources = 0x21,
+            InvalidResourceIdentifier = 0x22,
+        }
+        unsafe impl Discriminant<u8> for CommandSpecificStatus {}
 
-                if req.length != 4096 {
-                    debug!("Implement length support");
-                    return Err(ResponseStatus::InternalError);
-                }
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Virtualization Management");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
 
-                if req.offset != 0 {
-                    debug!("Implement offset support");
-                    return Err(ResponseStatus::InternalError);
+        let Some(ctlr) = subsys.ctlrs.iter_mut().find(|c| c.id.0 == ctx.ctlid) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        let Ok(config) =
+            TryInto::<AdminVirtualizationManagementConfiguration>::try_into(self.config)
+        else {
+            debug!("Invalid action for Admin Virtualization Management");
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        let nr = self.nr as u16;
+
+        let status = 'status: {
+            let resources = match config.act {
+                VirtualizationManagementAction::PrimaryFlexibleAllocation
+                | VirtualizationManagementAction::SecondaryAssign => {
+                    let Ok(rt) = TryInto::<ControllerResourceType>::try_into(config.rt) else {
+                        debug!("Invalid resource type: {}", config.rt);
+                        break 'status CommandSpecificStatus::InvalidResourceIdentifier;
+                    };
+                    Some((rt, nr))
                 }
+                VirtualizationManagementAction::SecondaryOffline
+                | VirtualizationManagementAction::SecondaryOnline => None,
+            };
 
-                let mh = MessageHeader::respond(MessageType::PcieCommand).encode()?;
+            let (pool, nrs): (&crate::FlexibleResources, u32) = match resources {
+                Some((ControllerResourceType::Vq, _)) => (
+                    &ctlr.virt.vq,
+                    ctlr.secondaries.iter().map(|sc| sc.nvq as u32).sum(),
+                ),
+                Some((ControllerResourceType::Vi, _)) => (
+                    &ctlr.virt.vi,
+                    ctlr.secondaries.iter().map(|sc| sc.nvi as u32).sum(),
+                ),
+                None => (&ctlr.virt.vq, 0),
+            };
 
-                let status = [0u8; 4]; /* Success */
+            match config.act {
+                VirtualizationManagementAction::PrimaryFlexibleAllocation => {
+                    if config.cntlid != ctlr.id.0 {
+                        debug!("CNTLID {} is not the primary controller", config.cntlid);
+                        break 'status CommandSpecificStatus::InvalidControllerIdentifier;
+                    }
 
-                let cr = PciDeviceFunctionConfigurationSpace::builder()
-                    .vid(subsys.info.pci_vid)
-                    .did(subsys.info.pci_did)
-                    .svid(subsys.info.pci_svid)
-                    .sdid(subsys.info.pci_sdid)
-                    .build()
-                    .encode()?;
+                    if nrs + nr as u32 > pool.frt {
+                        debug!("Insufficient flexible resources for primary: {nr}");
+                        break 'status CommandSpecificStatus::InvalidNumberOfControllerResources;
+                    }
+                }
+                VirtualizationManagementAction::SecondaryOffline
+                | VirtualizationManagementAction::SecondaryAssign
+                | VirtualizationManagementAction::SecondaryOnline => {
+                    let Some(sc) = ctlr.secondaries.iter().find(|sc| sc.id.0 == config.cntlid)
+                    else {
+                        debug!("CNTLID {} is not a secondary controller", config.cntlid);
+                        break 'status CommandSpecificStatus::InvalidControllerIdentifier;
+                    };
 
-                send_response(resp, &[&mh.0, &status, &cr.0]).await;
-                Ok(())
-            }
-            super::PcieCommandRequestType::ConfigurationWrite(req) => {
-                let response = if rest.len() == req.length as usize {
-                    debug!("Unsupported write at {} for {}", req.offset, req.length);
-                    ResponseStatus::AccessDenied
-                } else {
-                    debug!(
-                        "Request data size {} does not match requested write size {}",
-                        rest.len(),
-                        req.length
-                    );
-                    ResponseStatus::InvalidCommandInputDataSize
-                };
+                    if config.act == VirtualizationManagementAction::SecondaryAssign {
+                        if sc.online {
+                            debug!("Secondary controller {} is online", config.cntlid);
+                            break 'status CommandSpecificStatus::InvalidSecondaryControllerState;
+                        }
 
-                let mh = MessageHeader::respond(MessageType::PcieCommand).encode()?;
+                        let assigned = match resources {
+                            Some((ControllerResourceType::Vq, _)) => sc.nvq,
+                            _ => sc.nvi,
+                        };
 
-                let status = [response.id(), 0, 0, 0];
+                        if nr > pool.frsm
+                            || nrs - assigned as u32 + nr as u32 + pool.rfap as u32 > pool.frt
+                        {
+                            debug!("Invalid number of resources for secondary: {nr}");
+                            break 'status CommandSpecificStatus::InvalidNumberOfControllerResources;
+                        }
+                    }
 
-                send_response(resp, &[&mh.0, &status]).await;
-                Ok(())
+                    // A secondary controller requires an admin queue, an IO
+                    // queue and an interrupt vector to operate
+                    if config.act == VirtualizationManagementAction::SecondaryOnline
+                        && (sc.nvq < 2 || sc.nvi < 1)
+                    {
+                        debug!("Insufficient resources to online {}", config.cntlid);
+                        break 'status CommandSpecificStatus::InvalidSecondaryControllerState;
+                    }
+                }
             }
-            _ => {
+
+            app(CommandEffect::VirtualizationManagement {
+                cntlid: crate::ControllerId(config.cntlid),
+                act: config.act,
+                resources,
+            })
+            .await?;
+
+            // Only the resource actions carry resources
+            match (config.act, resources) {
+                (
+                    VirtualizationManagementAction::PrimaryFlexibleAllocation,
+                    Some((ControllerResourceType::Vq, nr)),
+                ) => ctlr.virt.vq.rfap = nr,
+                (
+                    VirtualizationManagementAction::PrimaryFlexibleAllocation,
+                    Some((ControllerResourceType::Vi, nr)),
+                ) => ctlr.virt.vi.rfap = nr,
+                (_, Some((rt, nr))) => {
+                    let Some(sc) = ctlr
+                        .secondaries
+                        .iter_mut()
+                        .find(|sc| sc.id.0 == config.cntlid)
+                    else {
+                        debug!("Secondary controller {} disappeared", config.cntlid);
+                        return Err(ResponseStatus::InternalError);
+                    };
+                    match rt {
+                        ControllerResourceType::Vq => sc.nvq = nr,
+                        ControllerResourceType::Vi => sc.nvi = nr,
+                    }
+                }
+                (_, None) => {
+                    let Some(sc) = ctlr
+                        .secondaries
+                        .iter_mut()
+                        .find(|sc| sc.id.0 == config.cntlid)
+                    else {
+                        debug!("Secondary controller {} disappeared", config.cntlid);
+                        return Err(ResponseStatus::InternalError);
+                    };
+                    sc.online = config.act == VirtualizationManagementAction::SecondaryOnline;
+                }
+            }
+
+            let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
+
+            let acrh = AdminCommandResponseHeader {
+                status: ResponseStatus::Success,
+                // Base v2.1, Virtualization Management, Completion Queue Entry Dword 0
+                cqedw0: resources.map(|(_, nr)| nr as u32).unwrap_or(0),
+                cqedw1: 0,
+                cqedw3: AdminIoCqeStatus {
+                    cid: 0,
+                    p: true,
+                    status: AdminIoCqeStatusType::GenericCommandStatus(
+                        AdminIoCqeGenericCommandStatus::SuccessfulCompletion,
+                    ),
+                    crd: crate::nvme::CommandRetryDelay::None,
+                    m: false,
+                    dnr: false,
+                }
+                .into(),
+            }
+            .encode()?;
+
+            return send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await;
+        };
+
+        admin_send_status(
+            resp,
+            mep.mic,
+            mep.csi,
+            AdminIoCqeStatusType::CommandSpecificStatus(status.id()),
+        )
+        .await
+    }
+}
+
+// Base v2.1, 5.1.25.1, Feature Identifiers
+const FID_TEMPERATURE_THRESHOLD: u8 = 0x04;
+const FID_NUMBER_OF_QUEUES: u8 = 0x07;
+const FID_ASYNCHRONOUS_EVENT_CONFIGURATION: u8 = 0x0b;
+
+// Base v2.1, 5.1.25, Temperature Threshold: TMPSEL in bits 19:16, THSEL in bits 21:20
+const TMPTH_COMPOSITE_OVER: u32 = 0x00;
+const TMPTH_COMPOSITE_UNDER: u32 = 0x10;
+
+// Base v2.1, 5.1.25, Command Specific Status Values
+const FEATURE_IDENTIFIER_NOT_SAVEABLE: u8 = 0x0d;
+
+impl RequestHandler for AdminSetFeaturesRequest {
+    type Ctx = AdminCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
+    {
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Set Features");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
+
+        let Some(ctlr) = subsys.ctlrs.iter_mut().find(|c| c.id.0 == ctx.ctlid) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        // Base v2.1, 5.1.25: FID in CDW10 bits 7:0, SV in bit 31
+        let fid = self.config as u8;
+        let sv = self.config & (1 << 31) != 0;
+
+        match fid {
+            FID_ASYNCHRONOUS_EVENT_CONFIGURATION => {
+                if sv {
+                    debug!("Asynchronous Event Configuration is not saveable");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::CommandSpecificStatus(
+                            FEATURE_IDENTIFIER_NOT_SAVEABLE,
+                        ),
+                    )
+                    .await;
+                }
+
+                // Only the SMART / Health Critical Warnings notices are supported
+                let Some(aec) = u8::try_from(self.value)
+                    .ok()
+                    .and_then(|v| FlagSet::new(v).ok())
+                else {
+                    debug!(
+                        "Unsupported asynchronous event configuration: {:#x}",
+                        self.value
+                    );
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                        ),
+                    )
+                    .await;
+                };
+
+                ctlr.aec = aec;
+                admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
+            }
+            FID_TEMPERATURE_THRESHOLD => {
+                if sv {
+                    debug!("Temperature Threshold is not saveable");
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::CommandSpecificStatus(
+                            FEATURE_IDENTIFIER_NOT_SAVEABLE,
+                        ),
+                    )
+                    .await;
+                }
+
+                // Base v2.1, 5.1.25: TMPTH in bits 15:0. Only the Composite Temperature
+                // thresholds are supported
+                let tmpth = self.value as u16;
+                let valid = match self.value >> 16 {
+                    TMPTH_COMPOSITE_OVER => ctlr.set_over_tmpth(tmpth).is_ok(),
+                    TMPTH_COMPOSITE_UNDER => ctlr.set_under_tmpth(tmpth).is_ok(),
+                    _ => false,
+                };
+
+                if !valid {
+                    debug!("Unsupported temperature threshold: {:#x}", self.value);
+                    return admin_send_status(
+                        resp,
+                        mep.mic,
+                        mep.csi,
+                        AdminIoCqeStatusType::GenericCommandStatus(
+                            AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                        ),
+                    )
+                    .await;
+                }
+
+                admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
+            }
+            _ => {
+                debug!("Unsupported feature identifier: {fid:#04x}");
+                admin_send_status(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    AdminIoCqeStatusType::GenericCommandStatus(
+                        AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                    ),
+                )
+                .await
+            }
+        }
+    }
+}
+
+impl RequestHandler for AdminGetFeaturesRequest {
+    type Ctx = AdminCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
+    {
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Get Features");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
+
+        let Some(ctlr) = subsys.ctlrs.iter().find(|c| c.id.0 == ctx.ctlid) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        // Base v2.1, 5.1.11: FID in CDW10 bits 7:0, SEL in bits 10:8
+        let fid = self.config as u8;
+        let sel = (self.config >> 8) & 0x7;
+
+        let value = match (fid, sel) {
+            // Base v2.1, 5.1.25, Number of Queues: NCQA in bits 31:16, NSQA in bits 15:0.
+            // Without I/O queues the counts are fixed: current, default and saved agree
+            (FID_NUMBER_OF_QUEUES, 0..=2) => ((ctlr.ncqa as u32) << 16) | ctlr.nsqa as u32,
+            // Supported Capabilities: Neither saveable nor changeable
+            (FID_NUMBER_OF_QUEUES, 3) => 0,
+            // Current
+            (FID_ASYNCHRONOUS_EVENT_CONFIGURATION, 0) => ctlr.aec.bits() as u32,
+            // Default, and Saved as the feature is not saveable
+            (FID_ASYNCHRONOUS_EVENT_CONFIGURATION, 1 | 2) => 0,
+            // Supported Capabilities: Changeable
+            (FID_ASYNCHRONOUS_EVENT_CONFIGURATION, 3) => 1 << 2,
+            // Current
+            (FID_TEMPERATURE_THRESHOLD, 0) if self.value >> 16 == TMPTH_COMPOSITE_OVER => {
+                ctlr.tmpth.upper as u32
+            }
+            (FID_TEMPERATURE_THRESHOLD, 0) if self.value >> 16 == TMPTH_COMPOSITE_UNDER => {
+                ctlr.tmpth.lower as u32
+            }
+            // Default, and Saved as the feature is not saveable
+            (FID_TEMPERATURE_THRESHOLD, 1 | 2) if self.value >> 16 == TMPTH_COMPOSITE_OVER => {
+                crate::DEFAULT_OVER_TEMPERATURE_THRESHOLD as u32
+            }
+            (FID_TEMPERATURE_THRESHOLD, 1 | 2) if self.value >> 16 == TMPTH_COMPOSITE_UNDER => {
+                crate::DEFAULT_UNDER_TEMPERATURE_THRESHOLD as u32
+            }
+            // Supported Capabilities: Changeable
+            (FID_TEMPERATURE_THRESHOLD, 3) => 1 << 2,
+            _ => {
+                debug!("Unsupported feature identifier {fid:#04x} or select {sel}");
+                return admin_send_status(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    AdminIoCqeStatusType::GenericCommandStatus(
+                        AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                    ),
+                )
+                .await;
+            }
+        };
+
+        let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
+
+        let acrh = AdminCommandResponseHeader {
+            status: ResponseStatus::Success,
+            cqedw0: value,
+            cqedw1: 0,
+            cqedw3: AdminIoCqeStatus {
+                cid: 0,
+                p: true,
+                status: AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::SuccessfulCompletion,
+                ),
+                crd: crate::nvme::CommandRetryDelay::None,
+                m: false,
+                dnr: false,
+            }
+            .into(),
+        }
+        .encode()?;
+
+        send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
+    }
+}
+
+impl RequestHandler for AdminAbortRequest {
+    type Ctx = AdminCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
+    {
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Abort");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
+
+        if !subsys.ctlrs.iter().any(|c| c.id.0 == ctx.ctlid) {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        }
+
+        // Commands complete synchronously, so there is never an outstanding command
+        // to abort
+        debug!(
+            "Not aborting CID {} on SQID {} for CTLID {}",
+            self.cid, self.sqid, ctx.ctlid
+        );
+
+        let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
+
+        let acrh = AdminCommandResponseHeader {
+            status: ResponseStatus::Success,
+            // Base v2.1, 5.1.1: DWORD 0 bit 0 set indicates the command was not aborted
+            cqedw0: 1,
+            cqedw1: 0,
+            cqedw3: AdminIoCqeStatus {
+                cid: 0,
+                p: true,
+                status: AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::SuccessfulCompletion,
+                ),
+                crd: crate::nvme::CommandRetryDelay::None,
+                m: false,
+                dnr: false,
+            }
+            .into(),
+        }
+        .encode()?;
+
+        send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
+    }
+}
+
+impl RequestHandler for AdminAsynchronousEventRequest {
+    type Ctx = AdminCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
+    {
+        // Base v2.1, 5.1.2
+        #[repr(u8)]
+        enum CommandSpecificStatus {
+            AsynchronousEventRequestLimitExceeded = 0x05,
+        }
+        unsafe impl Discriminant<u8> for CommandSpecificStatus {}
+
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Asynchronous Event Request");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
+
+        let Some(ctlr) = subsys.ctlrs.iter_mut().find(|c| c.id.0 == ctx.ctlid) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        // Base v2.1, 5.1.2 holds the request outstanding until an event occurs.
+        // Commands complete synchronously here, so without a pending event the
+        // request completes at once with Command Interrupted. DNR is clear, so the
+        // host may issue the request again to poll for events.
+        let Some(event) = ctlr.aens.into_iter().next() else {
+            debug!("No asynchronous events pending for CTLID {}", ctx.ctlid);
+
+            // Base v2.1, 5.1.13.2.1, Figure 312, AERL: Requests completed without an
+            // event count towards the 0's based limit of outstanding requests
+            if ctlr.aers > u16::from(ctlr.aerl) {
+                debug!(
+                    "Asynchronous Event Request limit exceeded for CTLID {}",
+                    ctx.ctlid
+                );
+                return admin_send_status(
+                    resp,
+                    mep.mic,
+                    mep.csi,
+                    AdminIoCqeStatusType::CommandSpecificStatus(
+                        CommandSpecificStatus::AsynchronousEventRequestLimitExceeded.id(),
+                    ),
+                )
+                .await;
+            }
+            ctlr.aers += 1;
+
+            let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
+
+            let acrh = AdminCommandResponseHeader {
+                status: ResponseStatus::Success,
+                cqedw0: 0,
+                cqedw1: 0,
+                cqedw3: AdminIoCqeStatus {
+                    cid: 0,
+                    p: true,
+                    status: AdminIoCqeStatusType::GenericCommandStatus(
+                        AdminIoCqeGenericCommandStatus::CommandInterrupted,
+                    ),
+                    crd: crate::nvme::CommandRetryDelay::None,
+                    m: false,
+                    dnr: false,
+                }
+                .into(),
+            }
+            .encode()?;
+
+            return send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await;
+        };
+        ctlr.aens -= event;
+
+        // Base v2.1, 5.1.2: SMART / Health Status event information
+        let aei: u32 = match event {
+            crate::nvme::mi::CriticalWarningFlags::St => 0x02,
+            crate::nvme::mi::CriticalWarningFlags::Taut => 0x01,
+            crate::nvme::mi::CriticalWarningFlags::Rd
+            | crate::nvme::mi::CriticalWarningFlags::Ro
+            | crate::nvme::mi::CriticalWarningFlags::Vmbf
+            | crate::nvme::mi::CriticalWarningFlags::Pmre => 0x00,
+        };
+
+        let mh = MessageHeader::respond(MessageType::NvmeAdminCommand, mep.csi).encode()?;
+
+        let acrh = AdminCommandResponseHeader {
+            status: ResponseStatus::Success,
+            // Base v2.1, 5.1.2: SMART / Health Status event type, with the
+            // SMART / Health Information log page
+            cqedw0: ((AdminGetLogPageLidRequestType::SmartHealthInformation.id() as u32) << 16)
+                | (aei << 8)
+                | 0x01,
+            cqedw1: 0,
+            cqedw3: AdminIoCqeStatus {
+                cid: 0,
+                p: true,
+                status: AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::SuccessfulCompletion,
+                ),
+                crd: crate::nvme::CommandRetryDelay::None,
+                m: false,
+                dnr: false,
+            }
+            .into(),
+        }
+        .encode()?;
+
+        send_response(resp, mep.mic, &[&mh.0, &acrh.0]).await
+    }
+}
+
+impl RequestHandler for AdminKeepAliveRequest {
+    type Ctx = AdminCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: AsyncRespChannel,
+    {
+        if !rest.is_empty() {
+            debug!("Invalid request size for Admin Keep Alive");
+            return Err(ResponseStatus::InvalidCommandSize);
+        }
+
+        let Some(ctlr) = subsys.ctlrs.iter_mut().find(|c| c.id.0 == ctx.ctlid) else {
+            debug!("Unrecognised CTLID: {}", ctx.ctlid);
+            return admin_send_status(
+                resp,
+                mep.mic,
+                mep.csi,
+                AdminIoCqeStatusType::GenericCommandStatus(
+                    AdminIoCqeGenericCommandStatus::InvalidFieldInCommand,
+                ),
+            )
+            .await;
+        };
+
+        // Base v2.1, 3.9: Restart the Keep Alive Timer
+        ctlr.keep_alive();
+
+        admin_send_response_body(resp, mep.mic, mep.csi, &[]).await
+    }
+}
+
+impl RequestHandler for PcieCommandRequestHeader {
+    type Ctx = PcieCommandRequestHeader;
+
+    async fn handle<A, C, const PORTS: usize, const CTLRS: usize, const NSS: usize>(
+        &self,
+        ctx: &Self::Ctx,
+        mep: &mut crate::GenericManagementEndpoint<CTLRS>,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        rest: &[u8],
+        resp: &mut C,
+        _app: A,
+    ) -> Result<(), ResponseStatus>
+    where
+        A: AsyncFnMut(crate::CommandEffect) -> Result<(), CommandEffectError>,
+        C: mctp::AsyncRespChannel,
+    {
+        match &ctx.op {
+            super::PcieCommandRequestType::ConfigurationRead(req) => {
+                if !rest.is_empty() {
+                    debug!("Invalid request size for PcieCommand");
+                    return Err(ResponseStatus::InvalidCommandSize);
+                }
+
+                let Some(ctlr) = subsys.ctlrs.get(ctx.ctlid as usize) else {
+                    debug!("Unrecognised controller ID: {}", ctx.ctlid);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(6, 0),
+                    ));
+                };
+
+                let start = req.offset as usize;
+                if start >= 4096 {
+                    debug!("Configuration read offset {} out of range", req.offset);
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(12, 0),
+                    ));
+                }
+
+                let Some(end) = start
+                    .checked_add(req.length as usize)
+                    .filter(|&end| req.length != 0 && end <= 4096)
+                else {
+                    debug!(
+                        "Configuration read length {} invalid for offset {}",
+                        req.length, req.offset
+                    );
+                    return Err(ResponseStatus::InvalidParameter(
+                        ParameterErrorLocation::new(8, 0),
+                    ));
+                };
+
+                let mh = MessageHeader::respond(MessageType::PcieCommand, mep.csi).encode()?;
+
+                let status = [0u8; 4]; /* Success */
+
+                let cr = ctlr.pcie.encode_into(&mut mep.scratch)?;
+
+                send_response(resp, mep.mic, &[&mh.0, &status, &cr.0[start..end]]).await
+            }
+            super::PcieCommandRequestType::ConfigurationWrite(req) => {
+                let response = if rest.len() == req.length as usize {
+                    debug!("Unsupported write at {} for {}", req.offset, req.length);
+                    ResponseStatus::AccessDenied
+                } else {
+                    debug!(
+                        "Request data size {} does not match requested write size {}",
+                        rest.len(),
+                        req.length
+                    );
+                    ResponseStatus::InvalidCommandInputDataSize
+                };
+
+                let mh = MessageHeader::respond(MessageType::PcieCommand, mep.csi).encode()?;
+
+                let status = [response.id(), 0, 0, 0];
+
+                send_response(resp, mep.mic, &[&mh.0, &status]).await
+            }
+            _ => {
                 debug!("Unimplemented OPCODE: {:?}", ctx._opcode);
                 Err(ResponseStatus::InternalError)
             }
@@ -2092,8 +3654,58 @@ impl RequestHandler for PcieCommandRequestHeader {
     }
 }
 
-impl crate::ManagementEndpoint {
-    fn update(&mut self, subsys: &crate::Subsystem) {
+// Presents a blocking response channel through the async interface so that
+// GenericManagementEndpoint::handle() can drive handle_async() to completion
+struct BlockingRespChannel<'c, C: mctp::RespChannel>(&'c mut C);
+
+struct BlockingReqChannel<R: mctp::ReqChannel>(R);
+
+impl<R: mctp::ReqChannel> mctp::AsyncReqChannel for BlockingReqChannel<R> {
+    async fn send_vectored(
+        &mut self,
+        typ: mctp::MsgType,
+        integrity_check: MsgIC,
+        bufs: &[&[u8]],
+    ) -> mctp::Result<()> {
+        self.0.send_vectored(typ, integrity_check, bufs)
+    }
+
+    async fn recv<'f>(
+        &mut self,
+        buf: &'f mut [u8],
+    ) -> mctp::Result<(mctp::MsgType, MsgIC, &'f mut [u8])> {
+        self.0.recv(buf)
+    }
+
+    fn remote_eid(&self) -> mctp::Eid {
+        self.0.remote_eid()
+    }
+}
+
+impl<C: mctp::RespChannel> AsyncRespChannel for BlockingRespChannel<'_, C> {
+    type ReqChannel<'a>
+        = BlockingReqChannel<C::ReqChannel>
+    where
+        Self: 'a;
+
+    async fn send_vectored(&mut self, integrity_check: MsgIC, bufs: &[&[u8]]) -> mctp::Result<()> {
+        self.0.send_vectored(integrity_check, bufs)
+    }
+
+    fn remote_eid(&self) -> mctp::Eid {
+        self.0.remote_eid()
+    }
+
+    fn req_channel(&self) -> mctp::Result<Self::ReqChannel<'_>> {
+        self.0.req_channel().map(BlockingReqChannel)
+    }
+}
+
+impl<const CTLRS: usize> crate::GenericManagementEndpoint<CTLRS> {
+    fn update<const PORTS: usize, const NSS: usize>(
+        &mut self,
+        subsys: &crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+    ) {
         assert!(subsys.ctlrs.len() <= self.mecss.len());
         for c in &subsys.ctlrs {
             let mecs = &mut self.mecss[c.id.0 as usize];
@@ -2122,6 +3734,26 @@ impl crate::ManagementEndpoint {
                 update |= crate::nvme::mi::ControllerHealthStatusChangedFlags::Rdy;
             }
 
+            if mecs.csts & crate::nvme::ControllerStatusFlags::ShstReserved
+                != c.csts & crate::nvme::ControllerStatusFlags::ShstReserved
+            {
+                update |= crate::nvme::mi::ControllerHealthStatusChangedFlags::Shst;
+            }
+
+            let raised = c.csts_latched - mecs.csts_latched;
+
+            if raised.contains(crate::nvme::mi::ControllerStatusFlags::Nac) {
+                update |= crate::nvme::mi::ControllerHealthStatusChangedFlags::Nac;
+            }
+
+            if raised.contains(crate::nvme::mi::ControllerStatusFlags::Fa) {
+                update |= crate::nvme::mi::ControllerHealthStatusChangedFlags::Fa;
+            }
+
+            if raised.contains(crate::nvme::mi::ControllerStatusFlags::Tcida) {
+                update |= crate::nvme::mi::ControllerHealthStatusChangedFlags::Tcida;
+            }
+
             mecs.chscf |= update;
 
             let update: CompositeControllerStatusFlagSet = update.into();
@@ -2129,85 +3761,304 @@ impl crate::ManagementEndpoint {
 
             mecs.cc = c.cc;
             mecs.csts = c.csts;
+            mecs.csts_latched = c.csts_latched;
         }
     }
 
-    pub async fn handle_async<
+    pub async fn handle_fragments_async<
+        'f,
+        I: IntoIterator<Item = &'f [u8]>,
         A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
         C: mctp::AsyncRespChannel,
+        const PORTS: usize,
+        const NSS: usize,
     >(
         &mut self,
-        subsys: &mut crate::Subsystem,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        frags: I,
+        buf: &mut [u8],
+        ic: MsgIC,
+        resp: C,
+        app: A,
+    ) {
+        let mut len = 0;
+        let mut tu = None;
+        let mut eom = false;
+
+        for frag in frags {
+            if frag.is_empty() {
+                debug!("Fragment at offset {len} carries no payload");
+                return;
+            }
+
+            // Only the final fragment may be shorter than the transmission unit
+            if eom {
+                debug!("Fragment at offset {len} follows end of message");
+                return;
+            }
+
+            match tu {
+                None => tu = Some(frag.len()),
+                Some(tu) if frag.len() > tu => {
+                    debug!(
+                        "Fragment at offset {len} exceeds transmission unit: {} > {tu}",
+                        frag.len()
+                    );
+                    return;
+                }
+                Some(tu) => eom = frag.len() < tu,
+            }
+
+            let Some(dst) = buf.get_mut(len..len + frag.len()) else {
+                debug!(
+                    "Reassembled message exceeds buffer capacity of {}",
+                    buf.len()
+                );
+                return;
+            };
+            dst.copy_from_slice(frag);
+            len += frag.len();
+        }
+
+        // The integrity check trails the message and must be wholly present
+        if len < 4 {
+            debug!("Reassembled message cannot contain a valid IC object");
+            return;
+        }
+
+        self.handle_async(subsys, &buf[..len], ic, resp, app).await
+    }
+
+    // Runs to completion in the caller's context, so resp must not rely on an executor
+    pub fn handle<
+        A: FnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: mctp::RespChannel,
+        const PORTS: usize,
+        const NSS: usize,
+    >(
+        &mut self,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
         msg: &[u8],
         ic: MsgIC,
         mut resp: C,
+        mut app: A,
+    ) {
+        let resp = BlockingRespChannel(&mut resp);
+        let mut fut = core::pin::pin!(self.handle_async(subsys, msg, ic, resp, async |e| app(e)));
+        let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+
+        // Each await point resolves through the blocking channel or callback,
+        // so the first poll is expected to complete. Nothing will wake the
+        // future, so any other outcome is an internal error.
+        if fut.as_mut().poll(&mut cx).is_pending() {
+            debug!("Internal error: request handling blocked, dropping the request");
+        }
+    }
+
+    pub async fn handle_async<
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: mctp::AsyncRespChannel,
+        const PORTS: usize,
+        const NSS: usize,
+    >(
+        &mut self,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        msg: &[u8],
+        ic: MsgIC,
+        resp: C,
         app: A,
     ) {
+        let _ = self.try_handle_async(subsys, msg, ic, resp, app).await;
+    }
+
+    // Ok if serviced, though the response may carry an error status
+    pub async fn try_handle_async<
+        A: AsyncFnMut(CommandEffect) -> Result<(), CommandEffectError>,
+        C: mctp::AsyncRespChannel,
+        const PORTS: usize,
+        const NSS: usize,
+    >(
+        &mut self,
+        subsys: &mut crate::GenericSubsystem<PORTS, CTLRS, NSS>,
+        msg: &[u8],
+        ic: MsgIC,
+        mut resp: C,
+        app: A,
+    ) -> Result<(), HandleError> {
+        let Some(port) = subsys.ports.iter().find(|p| p.id == self.port) else {
+            debug!(
+                "Management endpoint bound to unrecognised port ID: {}",
+                self.port.0
+            );
+            return Err(HandleError::PortUnrecognised);
+        };
+
+        if port.typ == crate::PortType::Inactive {
+            debug!(
+                "Management endpoint bound to inactive port ID: {}",
+                self.port.0
+            );
+            return Err(HandleError::PortInactive);
+        }
+
         self.update(subsys);
 
         if !ic.0 {
             debug!("NVMe-MI requires IC set for OOB messages");
-            return;
+            return Err(HandleError::IntegrityCheckRequired);
         }
 
         if msg.len() < 4 {
             debug!("Message cannot contain a valid IC object");
-            return;
+            return Err(HandleError::Truncated);
         }
 
         let Some((msg, icv)) = msg.split_at_checked(msg.len() - 4) else {
             debug!("Message too short to extract integrity check");
-            return;
+            return Err(HandleError::Truncated);
         };
 
-        let mut digest = ISCSI.digest();
-        digest.update(&[0x80 | 0x04]);
-        digest.update(msg);
-        let calculated = digest.finalize().to_le_bytes();
+        let calculated = self.mic.icv(&[msg]);
 
         if icv != calculated {
             debug!("checksum mismatch: {icv:02x?}, {calculated:02x?}");
-            return;
+            return Err(HandleError::IntegrityCheckFailed);
         }
 
         let Ok(((rest, _), mh)) = MessageHeader::from_bytes((msg, 0)) else {
             debug!("Message too short to extract NVMeMIMessageHeader");
-            return;
+            return Err(HandleError::Truncated);
         };
 
-        if mh.csi() {
-            debug!("Support second command slot");
-            return;
-        }
-
         if mh.ror() {
             debug!("NVMe-MI message was not a request: {:?}", mh.ror());
-            return;
+            return Err(HandleError::NotRequest);
         }
 
         let Ok(nmimt) = mh.nmimt() else {
             debug!("Message contains unrecognised NMIMT: {mh:x?}");
-            return;
+            return Err(HandleError::UnsupportedMessageType);
         };
 
+        // MI v2.0, 3.1.1, Figure 20, CSI
+        self.csi = mh.csi();
+
         if let Err(status) = mh.handle(&mh, self, subsys, rest, &mut resp, app).await {
-            let mut digest = ISCSI.digest();
-            digest.update(&[0x80 | 0x04]);
+            debug!(
+                "Responding with error status {status} ({:#04x})",
+                status.id()
+            );
 
-            let Ok(mh) = MessageHeader::respond(nmimt).encode() else {
+            let Ok(mh) = MessageHeader::respond(nmimt, self.csi).encode() else {
                 debug!("Failed to encode MessageHeader for error response");
-                return;
+                return Ok(());
             };
-            digest.update(&mh.0);
 
-            let ss: [u8; 4] = [status.id(), 0, 0, 0];
-            digest.update(&ss);
+            // MI v2.0, 4.1.2, Figure 30
+            let ss: [u8; 4] = match status {
+                ResponseStatus::InvalidParameter(pel) => {
+                    let [lo, hi] = pel.byte.to_le_bytes();
+                    [status.id(), pel.bit, lo, hi]
+                }
+                _ => [status.id(), 0, 0, 0],
+            };
 
-            let icv = digest.finalize().to_le_bytes();
+            let icv = self.mic.icv(&[&mh.0, &ss]);
             let respv = [mh.0.as_slice(), ss.as_slice(), icv.as_slice()];
+
+            #[cfg(feature = "trace-responses")]
+            Self::trace_response(&respv);
+
             if let Err(e) = resp.send_vectored(MsgIC(true), &respv).await {
                 debug!("Failed to send NVMe-MI error response: {e:?}");
             }
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mctp::{AsyncReqChannel, AsyncRespChannel, MsgIC};
+
+    use super::{MAX_FRAGMENTS, ResponseStatus, send_response};
+
+    struct NullReqChannel;
+
+    impl AsyncReqChannel for NullReqChannel {
+        async fn send_vectored(
+            &mut self,
+            _typ: mctp::MsgType,
+            _integrity_check: MsgIC,
+            _bufs: &[&[u8]],
+        ) -> mctp::Result<()> {
+            Ok(())
+        }
+
+        async fn recv<'f>(
+            &mut self,
+            buf: &'f mut [u8],
+        ) -> mctp::Result<(mctp::MsgType, MsgIC, &'f mut [u8])> {
+            Ok((mctp::MCTP_TYPE_NVME, MsgIC(true), buf))
+        }
+
+        fn remote_eid(&self) -> mctp::Eid {
+            mctp::Eid(9)
+        }
+    }
+
+    // Records the number of fragments in the sent response
+    #[derive(Default)]
+    struct FragmentCountRespChannel(Option<usize>);
+
+    impl AsyncRespChannel for FragmentCountRespChannel {
+        type ReqChannel<'a>
+            = NullReqChannel
+        where
+            Self: 'a;
+
+        async fn send_vectored(
+            &mut self,
+            _integrity_check: MsgIC,
+            bufs: &[&[u8]],
+        ) -> mctp::Result<()> {
+            self.0 = Some(bufs.len());
+            Ok(())
+        }
+
+        fn remote_eid(&self) -> mctp::Eid {
+            mctp::Eid(9)
+        }
+
+        fn req_channel(&self) -> mctp::Result<Self::ReqChannel<'_>> {
+            Ok(NullReqChannel)
+        }
+    }
+
+    #[test]
+    fn send_response_fragment_limit() {
+        let frag = [0u8; 4];
+        let bufs = [frag.as_slice(); MAX_FRAGMENTS];
+
+        // The integrity check occupies the final fragment
+        let mut resp = FragmentCountRespChannel::default();
+        let res = smol::block_on(send_response(
+            &mut resp,
+            crate::MessageIntegrityCheck::default(),
+            &bufs[..MAX_FRAGMENTS - 1],
+        ));
+        assert_eq!(res, Ok(()));
+        assert_eq!(resp.0, Some(MAX_FRAGMENTS));
+
+        // An overflow is reported so the caller can respond with an error
+        let mut resp = FragmentCountRespChannel::default();
+        let res = smol::block_on(send_response(
+            &mut resp,
+            crate::MessageIntegrityCheck::default(),
+            &bufs,
+        ));
+        assert_eq!(res, Err(ResponseStatus::InternalError));
+        assert_eq!(resp.0, None);
     }
 }
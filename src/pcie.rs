@@ -4,6 +4,13 @@
  */
 use deku::ctx::Endian;
 use deku::{DekuRead, DekuWrite};
+use log::debug;
+
+use crate::wire::WireVec;
+
+// The capability list immediately follows the header, and each capability
+// immediately follows its predecessor
+const CAPABILITIES_OFFSET: u8 = 0x40;
 
 // PCIe Base 4.0r1.0, 7.5.1.2, Figure 7-10
 #[derive(Debug, DekuRead, DekuWrite)]
@@ -31,7 +38,7 @@ pub struct PciDeviceFunctionConfigurationSpace {
     ip: u8,
     min_gnt: u8,
     max_lat: u8,
-    caps: [PciCapabilityType; 2],
+    caps: WireVec<PciCapabilityType, 5>,
 }
 impl crate::Encode<4096> for PciDeviceFunctionConfigurationSpace {}
 
@@ -53,14 +60,14 @@ impl PciDeviceFunctionConfigurationSpace {
             svid: 0xffff,
             sdid: 0xffff,
             rom: 0,
-            cap: 0x40,
+            cap: CAPABILITIES_OFFSET,
             il: 0,
             ip: 0,
             min_gnt: 0,
             max_lat: 0,
-            caps: [
+            caps: WireVec(heapless::Vec::from_iter([
                 PciCapabilityType::PciPowerManagement(PciPowerManagementCapability {
-                    next: 0x48,
+                    next: CAPABILITIES_OFFSET + PciPowerManagementCapability::LEN,
                     pmc: {
                         PowerManagementCapabilities {
                             version: 3,
@@ -78,13 +85,66 @@ impl PciDeviceFunctionConfigurationSpace {
                     data: 0,
                 }),
                 PciCapabilityType::Pcie(PcieCapability::default()),
-            ],
+            ])),
         }
     }
 
     pub fn builder() -> PciDeviceFunctionConfigurationSpaceBuilder {
         Default::default()
     }
+
+    pub fn vid(&self) -> u16 {
+        self.vid
+    }
+
+    pub fn did(&self) -> u16 {
+        self.did
+    }
+
+    pub fn svid(&self) -> u16 {
+        self.svid
+    }
+
+    pub fn sdid(&self) -> u16 {
+        self.sdid
+    }
+
+    // Reflect the PCIe port in the PCI Express Capability
+    pub(crate) fn set_port(&mut self, port: &crate::PciePort) {
+        for cap in self.caps.0.iter_mut() {
+            if let PciCapabilityType::Pcie(pcie) = cap {
+                pcie.set_port(port);
+            }
+        }
+    }
+
+    fn push_capability(&mut self, cap: PciCapabilityType) {
+        // Link the new capability from the current tail of the list
+        let mut next = CAPABILITIES_OFFSET;
+        for prev in self.caps.0.iter_mut() {
+            next += prev.len();
+            if prev.next() == 0 {
+                prev.set_next(next);
+            }
+        }
+
+        self.caps
+            .push(cap)
+            .expect("Capability list sized for all supported capabilities");
+    }
+
+    // VPD writes are unsupported: a write request (F set) completes by clearing F
+    pub fn write_vpd_address(&mut self, addr: u16) {
+        let Some(vpd) = self.caps.0.iter_mut().find_map(|cap| match cap {
+            PciCapabilityType::Vpd(vpd) => Some(vpd),
+            _ => None,
+        }) else {
+            debug!("No VPD Capability for VPD Address write: {addr:#06x}");
+            return;
+        };
+
+        vpd.write_address(addr);
+    }
 }
 
 impl Default for PciDeviceFunctionConfigurationSpace {
@@ -98,6 +158,10 @@ pub struct PciDeviceFunctionConfigurationSpaceBuilder {
     did: u16,
     svid: u16,
     sdid: u16,
+    port: Option<crate::PciePort>,
+    msi: Option<u8>,
+    msix: Option<MsixCapability>,
+    vpd: Option<&'static [u8]>,
 }
 
 impl Default for PciDeviceFunctionConfigurationSpaceBuilder {
@@ -107,6 +171,10 @@ impl Default for PciDeviceFunctionConfigurationSpaceBuilder {
             did: 0xffff,
             svid: 0xffff,
             sdid: 0xffff,
+            port: None,
+            msi: None,
+            msix: None,
+            vpd: None,
         }
     }
 }
@@ -132,14 +200,59 @@ impl PciDeviceFunctionConfigurationSpaceBuilder {
         self
     }
 
+    pub fn port(&mut self, port: &crate::PciePort) -> &mut Self {
+        self.port = Some(*port);
+        self
+    }
+
+    // Requests 2^mmc vectors
+    pub fn msi(&mut self, mmc: u8) -> &mut Self {
+        self.msi = Some(mmc);
+        self
+    }
+
+    // Table size is 0's based
+    pub fn msix(&mut self, ts: u16, tbir: u8, to: u32, pbir: u8, pbao: u32) -> &mut Self {
+        self.msix = Some(MsixCapability::new(ts, tbir, to, pbir, pbao));
+        self
+    }
+
+    pub fn vpd(&mut self, vpd: &'static [u8]) -> &mut Self {
+        self.vpd = Some(vpd);
+        self
+    }
+
     pub fn build(&self) -> PciDeviceFunctionConfigurationSpace {
-        PciDeviceFunctionConfigurationSpace {
+        let mut cs = PciDeviceFunctionConfigurationSpace {
             vid: self.vid,
             did: self.did,
             svid: self.svid,
             sdid: self.sdid,
             ..Default::default()
+        };
+
+        if let Some(port) = &self.port {
+            cs.set_port(port);
+        }
+
+        if let Some(mmc) = self.msi {
+            cs.push_capability(PciCapabilityType::Msi(MsiCapability::new(mmc)));
         }
+
+        if let Some(msix) = &self.msix {
+            cs.push_capability(PciCapabilityType::Msix(msix.clone()));
+        }
+
+        if let Some(vpd) = self.vpd {
+            cs.push_capability(PciCapabilityType::Vpd(VpdCapability {
+                next: 0,
+                addr: 0,
+                data: 0,
+                vpd,
+            }));
+        }
+
+        cs
     }
 }
 
@@ -178,6 +291,10 @@ pub struct PciPowerManagementCapability {
     data: u8,
 }
 
+impl PciPowerManagementCapability {
+    const LEN: u8 = 8;
+}
+
 #[derive(Debug, Default, DekuRead, DekuWrite)]
 #[deku(ctx = "endian: Endian", endian = "endian")]
 pub struct PcieCapability {
@@ -204,13 +321,166 @@ pub struct PcieCapability {
     slotsts2: u16,
 }
 
+impl PcieCapability {
+    fn set_port(&mut self, port: &crate::PciePort) {
+        // PCIe Base 4.0r1.0, 7.5.3.3: Encoded as for PCIEMPS
+        self.devcap = (self.devcap & !0x7) | (u8::from(port.mps) as u32 & 0x7);
+
+        // PCIe Base 4.0r1.0, 7.5.3.6: Encoded as for PCIECLS
+        self.linkcap = (self.linkcap & !0x3ff)
+            | ((u8::from(port.mlw) as u32 & 0x3f) << 4)
+            | (u8::from(port.mls) as u32 & 0xf);
+
+        // PCIe Base 4.0r1.0, 7.5.3.8
+        self.linksts = (self.linksts & !0x3ff)
+            | ((port.negotiated_link_width() as u16 & 0x3f) << 4)
+            | (port.current_link_speed() as u16 & 0xf);
+
+        // PCIe Base 4.0r1.0, 7.5.3.18, Supported Link Speeds Vector
+        self.linkcap2 = (self.linkcap2 & !0xfe) | ((port.supported_link_speeds() as u32) << 1);
+    }
+}
+
+// PCIe Base 4.0r1.0, 7.7.1, Figure 7-81
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct MsiCapability {
+    next: u8,
+    mc: u16,
+    ma: u32,
+    mua: u32,
+    md: u16,
+    emd: u16,
+    mask: u32,
+    pending: u32,
+}
+
+impl MsiCapability {
+    // PCIe Base 4.0r1.0, 7.7.1.2
+    const MC_64BIT: u16 = 1 << 7;
+    const MC_PVM: u16 = 1 << 8;
+
+    fn new(mmc: u8) -> Self {
+        // The register layout implies 64-bit addressing and per-vector masking
+        Self {
+            next: 0,
+            mc: Self::MC_PVM | Self::MC_64BIT | ((mmc as u16 & 0x7) << 1),
+            ma: 0,
+            mua: 0,
+            md: 0,
+            emd: 0,
+            mask: 0,
+            pending: 0,
+        }
+    }
+}
+
+// PCIe Base 4.0r1.0, 7.7.2, Figure 7-91
+#[derive(Clone, Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct MsixCapability {
+    next: u8,
+    mc: u16,
+    tbir: u32,
+    pbir: u32,
+}
+
+impl MsixCapability {
+    fn new(ts: u16, tbir: u8, to: u32, pbir: u8, pbao: u32) -> Self {
+        // PCIe Base 4.0r1.0, 7.7.2.3, 7.7.2.4: QWORD aligned, sharing the BIR
+        Self {
+            next: 0,
+            mc: ts & 0x7ff,
+            tbir: (to & !7) | (tbir as u32 & 7),
+            pbir: (pbao & !7) | (pbir as u32 & 7),
+        }
+    }
+}
+
+// PCIe Base 4.0r1.0, 7.9.19
+#[derive(Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct VpdCapability {
+    next: u8,
+    addr: u16,
+    data: u32,
+    #[deku(skip)]
+    vpd: &'static [u8],
+}
+
+impl VpdCapability {
+    // PCIe Base 4.0r1.0, 7.9.19.2, F
+    const F: u16 = 1 << 15;
+
+    fn write_address(&mut self, addr: u16) {
+        if addr & Self::F != 0 {
+            debug!(
+                "Discarding unsupported VPD write at {:#06x}",
+                addr & !Self::F
+            );
+            self.addr = addr & !Self::F;
+            return;
+        }
+
+        // VPD is accessed in dwords; reads beyond the image return zeros
+        let mut dw = [0u8; 4];
+        let start = addr as usize & !3;
+        if let Some(src) = self.vpd.get(start..) {
+            let len = core::cmp::min(src.len(), dw.len());
+            dw[..len].copy_from_slice(&src[..len]);
+        }
+
+        self.data = u32::from_le_bytes(dw);
+        self.addr = addr | Self::F;
+    }
+}
+
 #[derive(Debug, DekuRead, DekuWrite)]
 #[deku(ctx = "endian: Endian", endian = "endian", id_type = "u8")]
 #[repr(u8)]
 pub enum PciCapabilityType {
     #[deku(id = "0x01")]
     PciPowerManagement(PciPowerManagementCapability),
+    #[deku(id = "0x03")]
+    Vpd(VpdCapability),
+    #[deku(id = "0x05")]
+    Msi(MsiCapability),
     #[deku(id = "0x10")]
     Pcie(PcieCapability),
+    #[deku(id = "0x11")]
+    Msix(MsixCapability),
 }
 unsafe impl crate::Discriminant<u8> for PciCapabilityType {}
+
+impl PciCapabilityType {
+    // Encoded length, including the Capability ID
+    fn len(&self) -> u8 {
+        match self {
+            PciCapabilityType::PciPowerManagement(_) => PciPowerManagementCapability::LEN,
+            PciCapabilityType::Vpd(_) => 8,
+            PciCapabilityType::Msi(_) => 24,
+            PciCapabilityType::Pcie(_) => 0x34,
+            PciCapabilityType::Msix(_) => 12,
+        }
+    }
+
+    fn next(&self) -> u8 {
+        match self {
+            PciCapabilityType::PciPowerManagement(cap) => cap.next,
+            PciCapabilityType::Vpd(cap) => cap.next,
+            PciCapabilityType::Msi(cap) => cap.next,
+            PciCapabilityType::Pcie(cap) => cap.next,
+            PciCapabilityType::Msix(cap) => cap.next,
+        }
+    }
+
+    fn set_next(&mut self, next: u8) {
+        match self {
+            PciCapabilityType::PciPowerManagement(cap) => cap.next = next,
+            PciCapabilityType::Vpd(cap) => cap.next = next,
+            PciCapabilityType::Msi(cap) => cap.next = next,
+            PciCapabilityType::Pcie(cap) => cap.next = next,
+            PciCapabilityType::Msix(cap) => cap.next = next,
+        }
+    }
+}
@@ -10,3 +10,5 @@ mod uuid;
 pub use uuid::WireUuid;
 mod vec;
 pub use vec::WireVec;
+mod window;
+pub use window::WindowWriter;
@@ -32,6 +32,12 @@ impl<const S: usize> WireString<S> {
     }
 }
 
+impl<const S: usize> Default for WireString<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, Ctx, const S: usize> DekuReader<'a, Ctx> for WireString<S>
 where
     Ctx: Copy,
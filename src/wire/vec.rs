@@ -112,7 +112,10 @@ where
                     count == 0
                 })
             }
-            Limit::Until(_, _phantom_data) => todo!(),
+            Limit::Until(mut predicate, _phantom_data) => {
+                // Read until the predicate matches, including the matched element
+                reader_vec_with_predicate(reader, None, inner_ctx, move |_, value| predicate(value))
+            }
             Limit::ByteSize(size) => {
                 let bit_size = size.0 * 8;
 
@@ -125,8 +128,38 @@ where
                     read_bits == bit_size
                 })
             }
-            Limit::BitSize(_size) => todo!(),
-            Limit::End => todo!(),
+            Limit::BitSize(size) => {
+                let bit_size = size.0;
+
+                // Handle the trivial case of reading an empty vector
+                if bit_size == 0 {
+                    return Ok(WireVec::new());
+                }
+
+                reader_vec_with_predicate(reader, None, inner_ctx, move |read_bits, _| {
+                    read_bits == bit_size
+                })
+            }
+            Limit::End => {
+                // Reading zero-sized items never reaches the end of the reader
+                if mem::size_of::<T>() == 0 {
+                    return Ok(WireVec::new());
+                }
+
+                let mut res = WireVec::new();
+
+                while !reader.end() {
+                    let val = <T>::from_reader_with_ctx(reader, inner_ctx)?;
+                    if res.push(val).is_err() {
+                        return Err(deku_error!(
+                            DekuError::InvalidParam,
+                            "Failed to insert item into WireVec"
+                        ));
+                    };
+                }
+
+                Ok(res)
+            }
         }
     }
 }
@@ -172,3 +205,165 @@ impl<T: DekuWriter<Ctx>, Ctx: Copy, const S: usize> DekuWriter<Ctx> for WireVec<
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use deku::{
+        DekuReader, DekuWriter,
+        ctx::{BitSize, Endian, Limit},
+        no_std_io::Cursor,
+        reader::Reader,
+        writer::Writer,
+    };
+
+    use crate::wire::vec::WireVec;
+
+    #[test]
+    fn until_zero() {
+        let test_data = [1u8, 0, 2, 0, 0, 0, 3, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::new_until(|v: &u16| *v == 0), Endian::Little),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.0.as_slice(), &[1, 2, 0]);
+    }
+
+    #[test]
+    fn until_zero_first() {
+        let test_data = [0u8, 0, 1, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::new_until(|v: &u16| *v == 0), Endian::Little),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.0.as_slice(), &[0]);
+    }
+
+    #[test]
+    fn until_zero_overflow() {
+        let test_data = [1u8, 0, 2, 0, 3, 0, 0, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 2>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::new_until(|v: &u16| *v == 0), Endian::Little),
+        );
+
+        assert!(deku_test.is_err());
+    }
+
+    #[test]
+    fn until_zero_unterminated() {
+        let test_data = [1u8, 0, 2, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::new_until(|v: &u16| *v == 0), Endian::Little),
+        );
+
+        assert!(deku_test.is_err());
+    }
+
+    #[test]
+    fn end() {
+        let test_data = [1u8, 0, 2, 0, 3, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::<u16, fn(&u16) -> bool>::end(), Endian::Little),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.0.as_slice(), &[1, 2, 3]);
+
+        let mut ret_data = [0xffu8; 6];
+        let mut cursor = Cursor::new(ret_data.as_mut_slice());
+        let mut writer = Writer::new(&mut cursor);
+        deku_test.to_writer(&mut writer, Endian::Little).unwrap();
+
+        assert_eq!(test_data, ret_data);
+    }
+
+    #[test]
+    fn end_empty() {
+        let test_data = [0u8; 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::<u16, fn(&u16) -> bool>::end(), Endian::Little),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.len(), 0);
+    }
+
+    #[test]
+    fn end_overflow() {
+        let test_data = [1u8, 0, 2, 0, 3, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 2>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::<u16, fn(&u16) -> bool>::end(), Endian::Little),
+        );
+
+        assert!(deku_test.is_err());
+    }
+
+    #[test]
+    fn end_zero_sized() {
+        let test_data = [1u8, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<(), 4>::from_reader_with_ctx(
+            &mut reader,
+            (Limit::<(), fn(&()) -> bool>::end(), Endian::Little),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.len(), 0);
+    }
+
+    #[test]
+    fn bit_size() {
+        let test_data = [1u8, 0, 2, 0, 3, 0];
+
+        let mut cursor = Cursor::new(&test_data);
+        let mut reader = Reader::new(&mut cursor);
+        let deku_test = WireVec::<u16, 4>::from_reader_with_ctx(
+            &mut reader,
+            (
+                Limit::<u16, fn(&u16) -> bool>::from(BitSize(32)),
+                Endian::Little,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(deku_test.0.as_slice(), &[1, 2]);
+
+        let mut ret_data = [0xffu8; 4];
+        let mut cursor = Cursor::new(ret_data.as_mut_slice());
+        let mut writer = Writer::new(&mut cursor);
+        deku_test.to_writer(&mut writer, Endian::Little).unwrap();
+
+        assert_eq!(test_data[..4], ret_data);
+    }
+}
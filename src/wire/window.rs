@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-3.0-only
+/*
+ * Copyright (c) 2025 Code Construct
+ */
+use deku::no_std_io::{self, Seek, SeekFrom, Write};
+
+// Retains only the bytes written within [offset, offset + buf.len()) of the
+// encoded stream, allowing a response to be sliced without materialising it
+pub struct WindowWriter<'a> {
+    offset: u64,
+    pos: u64,
+    buf: &'a mut [u8],
+}
+
+impl<'a> WindowWriter<'a> {
+    pub fn new(offset: usize, buf: &'a mut [u8]) -> Self {
+        Self {
+            offset: offset as u64,
+            pos: 0,
+            buf,
+        }
+    }
+}
+
+impl Write for WindowWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> no_std_io::Result<usize> {
+        let start = self.pos;
+        let end = start + data.len() as u64;
+        let wstart = self.offset;
+        let wend = self.offset + self.buf.len() as u64;
+
+        if start < wend && end > wstart {
+            let from = start.max(wstart);
+            let to = end.min(wend);
+            let dst = (from - wstart) as usize..(to - wstart) as usize;
+            let src = (from - start) as usize..(to - start) as usize;
+            self.buf[dst].copy_from_slice(&data[src]);
+        }
+
+        self.pos = end;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> no_std_io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for WindowWriter<'_> {
+    fn seek(&mut self, style: SeekFrom) -> no_std_io::Result<u64> {
+        let pos = match style {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+            SeekFrom::End(_) => None,
+        };
+
+        let Some(pos) = pos else {
+            return Err(no_std_io::Error::new(
+                no_std_io::ErrorKind::InvalidInput,
+                "Invalid seek in window",
+            ));
+        };
+
+        self.pos = pos;
+        Ok(pos)
+    }
+}
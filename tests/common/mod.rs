@@ -35,6 +35,30 @@ impl mctp::AsyncReqChannel for MockNVMeMIAsyncReqChannel {
     }
 }
 
+pub struct MockNVMeMIReqChannel {}
+
+impl mctp::ReqChannel for MockNVMeMIReqChannel {
+    fn send_vectored(
+        &mut self,
+        _typ: mctp::MsgType,
+        _integrity_check: MsgIC,
+        _bufs: &[&[u8]],
+    ) -> mctp::Result<()> {
+        Result::Ok(())
+    }
+
+    fn recv<'f>(
+        &mut self,
+        buf: &'f mut [u8],
+    ) -> mctp::Result<(mctp::MsgType, MsgIC, &'f mut [u8])> {
+        mctp::Result::Ok((mctp::MCTP_TYPE_NVME, MsgIC(true), buf))
+    }
+
+    fn remote_eid(&self) -> mctp::Eid {
+        todo!()
+    }
+}
+
 pub struct NeverRespChannel {
     msg: &'static str,
 }
@@ -91,13 +115,8 @@ impl Drop for ExpectedRespChannel<'_> {
     }
 }
 
-impl mctp::AsyncRespChannel for ExpectedRespChannel<'_> {
-    type ReqChannel<'a>
-        = MockNVMeMIAsyncReqChannel
-    where
-        Self: 'a;
-
-    async fn send_vectored(&mut self, _integrity_check: MsgIC, bufs: &[&[u8]]) -> mctp::Result<()> {
+impl ExpectedRespChannel<'_> {
+    fn check(&mut self, bufs: &[&[u8]]) -> mctp::Result<()> {
         self.sent = true;
 
         assert_eq!(
@@ -117,6 +136,17 @@ impl mctp::AsyncRespChannel for ExpectedRespChannel<'_> {
         );
         Ok(())
     }
+}
+
+impl mctp::AsyncRespChannel for ExpectedRespChannel<'_> {
+    type ReqChannel<'a>
+        = MockNVMeMIAsyncReqChannel
+    where
+        Self: 'a;
+
+    async fn send_vectored(&mut self, _integrity_check: MsgIC, bufs: &[&[u8]]) -> mctp::Result<()> {
+        self.check(bufs)
+    }
 
     fn remote_eid(&self) -> mctp::Eid {
         mctp::Eid(9)
@@ -127,6 +157,22 @@ impl mctp::AsyncRespChannel for ExpectedRespChannel<'_> {
     }
 }
 
+impl mctp::RespChannel for ExpectedRespChannel<'_> {
+    type ReqChannel = MockNVMeMIReqChannel;
+
+    fn send_vectored(&mut self, _integrity_check: MsgIC, bufs: &[&[u8]]) -> mctp::Result<()> {
+        self.check(bufs)
+    }
+
+    fn remote_eid(&self) -> mctp::Eid {
+        mctp::Eid(9)
+    }
+
+    fn req_channel(&self) -> mctp::Result<Self::ReqChannel> {
+        todo!()
+    }
+}
+
 /// A tuple of `(byte_offset, expected_slice)`
 pub type ExpectedField<'a> = (usize, &'a [u8]);
 pub struct RelaxedRespChannel<'a> {
@@ -179,6 +225,7 @@ impl mctp::AsyncRespChannel for RelaxedRespChannel<'_> {
     }
 }
 
+#[allow(dead_code)]
 pub fn setup() {
     if true {
         let _ = TermLogger::init(
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: GPL-3.0-only
+/*
+ * Copyright (c) 2025 Code Construct
+ */
+#![cfg(feature = "decode")]
+
+use nvme_mi_dev::nvme::mi::decode::{
+    self, DecodeError, NvmSubsystemHealth, NvmSubsystemInformation,
+};
+
+#[rustfmt::skip]
+const RESP_NVM_SUBSYSTEM_INFORMATION: [u8; 43] = [
+    0x88, 0x00, 0x00,
+    0x00, 0x20, 0x00, 0x00,
+    0x01, 0x01, 0x02, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x3c, 0xf8, 0xdb, 0x52
+];
+
+#[rustfmt::skip]
+const RESP_HEALTH_STATUS_POLL: [u8; 19] = [
+    0x88, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x38, 0x3d, 0x14, 0x26,
+    0x00, 0x00, 0x00, 0x00,
+    0x11, 0x7c, 0xb0, 0x3d
+];
+
+#[test]
+fn nvm_subsystem_information() {
+    let rsp = decode::response(&RESP_NVM_SUBSYSTEM_INFORMATION).unwrap();
+    assert!(!rsp.csi());
+    assert_eq!(
+        decode::nvm_subsystem_information(&rsp),
+        Ok(NvmSubsystemInformation {
+            nump: 1,
+            mjr: 1,
+            mnr: 2,
+            nnsc: 0,
+        })
+    );
+}
+
+#[test]
+fn subsystem_health_status_poll() {
+    let rsp = decode::response(&RESP_HEALTH_STATUS_POLL).unwrap();
+    assert_eq!(
+        decode::subsystem_health_status_poll(&rsp),
+        Ok(NvmSubsystemHealth {
+            nss: 0x38,
+            sw: 0x3d,
+            ctemp: 0x14,
+            pldu: 0x26,
+            ccsf: 0,
+        })
+    );
+}
+
+#[test]
+fn integrity_check_failed() {
+    let mut resp = RESP_NVM_SUBSYSTEM_INFORMATION;
+    resp[7] ^= 0xff;
+    assert_eq!(
+        decode::response(&resp).unwrap_err(),
+        DecodeError::IntegrityCheckFailed
+    );
+}
+
+#[test]
+fn truncated() {
+    assert_eq!(
+        decode::response(&[0x88, 0x00, 0x00]).unwrap_err(),
+        DecodeError::Truncated
+    );
+}
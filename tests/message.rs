@@ -3,10 +3,15 @@
  * Copyright (c) 2025 Code Construct
  */
 use mctp::MsgIC;
+use nvme_mi_dev::{
+    HandleError, ManagementEndpoint, MessageIntegrityCheck, PciePort, PortType, Subsystem,
+    SubsystemInfo, TwoWirePort,
+};
 mod common;
 
 use common::DeviceType;
 
+use crate::common::ExpectedRespChannel;
 use crate::common::NeverRespChannel;
 use crate::common::new_device;
 use crate::common::setup;
@@ -89,3 +94,300 @@ fn invalid_message_header_ror() {
             .await
     });
 }
+
+#[test]
+fn management_endpoint_unrecognised_port() {
+    setup();
+
+    let mut other = Subsystem::new(SubsystemInfo::invalid());
+    let _ = other.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let twpid = other
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    subsys.add_controller(ppid).unwrap();
+
+    let resp = NeverRespChannel::new("Response sent by endpoint bound to unrecognised port");
+
+    #[rustfmt::skip]
+    const REQ: [u8; 19] = [
+        0x08, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0xe2, 0x00, 0x06, 0x07
+    ];
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    });
+}
+
+#[test]
+fn second_command_slot() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 19] = [
+        0x09, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0xe3, 0xfd, 0x88, 0x60
+    ];
+
+    #[rustfmt::skip]
+    const RESP: [u8; 43] = [
+        0x89, 0x00, 0x00,
+        0x00, 0x20, 0x00, 0x00,
+        0x01, 0x01, 0x02, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x42, 0xf9, 0x25, 0x72
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    });
+}
+
+#[rustfmt::skip]
+const REQ_HEALTH_STATUS_POLL: [u8; 19] = [
+    0x08, 0x00, 0x00,
+    0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x80,
+    0xaa, 0xef, 0x81, 0xb4
+];
+
+#[rustfmt::skip]
+const RESP_HEALTH_STATUS_POLL: [u8; 19] = [
+    0x88, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x38, 0x3d, 0x14, 0x26,
+    0x00, 0x00, 0x00, 0x00,
+    0x11, 0x7c, 0xb0, 0x3d
+];
+
+#[test]
+fn fragmented_request() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let resp = ExpectedRespChannel::new(&RESP_HEALTH_STATUS_POLL);
+    smol::block_on(async {
+        mep.handle_async(
+            &mut subsys,
+            &REQ_HEALTH_STATUS_POLL,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let mut buf = [0u8; 64];
+    let frags = REQ_HEALTH_STATUS_POLL.chunks(8);
+    assert_eq!(frags.len(), 3);
+    let resp = ExpectedRespChannel::new(&RESP_HEALTH_STATUS_POLL);
+    smol::block_on(async {
+        mep.handle_fragments_async(
+            &mut subsys,
+            frags,
+            &mut buf,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+}
+
+#[test]
+fn fragmented_request_short_middle() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let mut buf = [0u8; 64];
+    let (head, tail) = REQ_HEALTH_STATUS_POLL.split_at(8);
+    let (middle, tail) = tail.split_at(4);
+    let resp = NeverRespChannel::new("Response sent for request with short middle fragment");
+    smol::block_on(async {
+        mep.handle_fragments_async(
+            &mut subsys,
+            [head, middle, tail],
+            &mut buf,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+}
+
+#[test]
+fn fragmented_request_overflow() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let mut buf = [0u8; 16];
+    let resp = NeverRespChannel::new("Response sent for request exceeding reassembly buffer");
+    smol::block_on(async {
+        mep.handle_fragments_async(
+            &mut subsys,
+            REQ_HEALTH_STATUS_POLL.chunks(8),
+            &mut buf,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+}
+
+#[test]
+fn enumerate_device() {
+    setup();
+
+    let (_, subsys) = new_device(DeviceType::P1p1tC1iN1a1a);
+
+    let ports: Vec<_> = subsys.ports().collect();
+    assert_eq!(ports.len(), 2);
+    assert!(matches!(ports[0].typ(), PortType::Pcie(_)));
+    assert!(matches!(ports[1].typ(), PortType::TwoWire(_)));
+
+    let ctlrs: Vec<_> = subsys.controllers().collect();
+    assert_eq!(ctlrs.len(), 1);
+    assert_eq!(ctlrs[0].port(), ports[0].id());
+
+    let nss: Vec<_> = subsys.namespaces().collect();
+    assert_eq!(nss.len(), 1);
+    assert_eq!(nss[0].capacity(), 1024);
+    assert_eq!(nss[0].size(), 1024);
+    assert_eq!(
+        ctlrs[0].active_namespaces().copied().collect::<Vec<_>>(),
+        [nss[0].id()]
+    );
+}
+
+#[test]
+fn enumerate_device_no_namespaces() {
+    setup();
+
+    let (_, subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    assert_eq!(subsys.ports().count(), 2);
+    assert_eq!(subsys.controllers().count(), 1);
+    assert_eq!(subsys.namespaces().count(), 0);
+    assert_eq!(
+        subsys
+            .controllers()
+            .flat_map(|c| c.active_namespaces())
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn drop_reasons() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    let cases: [(&[u8], MsgIC, HandleError); 5] = [
+        (&[], MsgIC(false), HandleError::IntegrityCheckRequired),
+        (&[0x00, 0x00, 0x00], MsgIC(true), HandleError::Truncated),
+        (&[!0x36, !0xff, !0x11, !0x17], MsgIC(true), HandleError::IntegrityCheckFailed),
+        (&[0x80, 0x00, 0x00, 0x48, 0xc4, 0xc2, 0xea], MsgIC(true), HandleError::NotRequest),
+        (&[0x18, 0x00, 0x00, 0x44, 0xf0, 0x16, 0x29], MsgIC(true), HandleError::UnsupportedMessageType),
+    ];
+
+    for (req, ic, err) in cases {
+        let resp = NeverRespChannel::new("Response sent for dropped request");
+        let res = smol::block_on(async {
+            mep.try_handle_async(&mut subsys, req, ic, resp, async |_| Ok(()))
+                .await
+        });
+        assert_eq!(res, Err(err));
+    }
+}
+
+#[test]
+fn serviced_request() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let resp = ExpectedRespChannel::new(&RESP_HEALTH_STATUS_POLL);
+    let res = smol::block_on(async {
+        mep.try_handle_async(
+            &mut subsys,
+            &REQ_HEALTH_STATUS_POLL,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn message_integrity_check_default() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+    mep.set_message_integrity_check(MessageIntegrityCheck::default());
+
+    let resp = ExpectedRespChannel::new(&RESP_HEALTH_STATUS_POLL);
+    let res = smol::block_on(async {
+        mep.try_handle_async(
+            &mut subsys,
+            &REQ_HEALTH_STATUS_POLL,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn message_integrity_check_custom() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+    mep.set_message_integrity_check(MessageIntegrityCheck::new(0, 0x84));
+
+    let resp = NeverRespChannel::new("Response sent for request with default integrity check");
+    let res = smol::block_on(async {
+        mep.try_handle_async(
+            &mut subsys,
+            &REQ_HEALTH_STATUS_POLL,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+    assert_eq!(res, Err(HandleError::IntegrityCheckFailed));
+}
@@ -7,7 +7,15 @@ mod common;
 use common::setup;
 use mctp::MsgIC;
 
-use crate::common::{DeviceType, ExpectedRespChannel, new_device};
+use nvme_mi_dev::{
+    ManagementEndpoint, PciePort, PortType, Subsystem, SubsystemInfo, TwoWirePort,
+    nvme::mi::{PcieLinkSpeed, PcieLinkWidth},
+    pcie::PciDeviceFunctionConfigurationSpace,
+};
+
+use crate::common::{
+    DeviceType, ExpectedField, ExpectedRespChannel, RelaxedRespChannel, TestDevice, new_device,
+};
 
 #[rustfmt::skip]
 pub const RESP_INVALID_COMMAND_SIZE: [u8; 11] = [
@@ -130,15 +138,15 @@ fn configuration_read() {
         0x10, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
+        0x26, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x11, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
-        0x00, 0x00, 0x00, 0x00,
-        0x00, 0x00, 0x00, 0x00,
-        0x00, 0x00, 0x00, 0x00,
+        0x7e, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
         0x00, 0x00, 0x00, 0x00,
@@ -148,7 +156,7 @@ fn configuration_read() {
     let mut resp = [0; RESP_LEN];
     resp[0] = 0xa0;
     resp[7..139].copy_from_slice(&PCI_CONFIG);
-    resp[{ RESP_LEN - 4 }..].copy_from_slice(&[0xab, 0x72, 0xdb, 0xa3]);
+    resp[{ RESP_LEN - 4 }..].copy_from_slice(&[0xd4, 0x31, 0x21, 0xb0]);
 
     let resp = ExpectedRespChannel::new(&resp);
     smol::block_on(async {
@@ -157,6 +165,595 @@ fn configuration_read() {
     })
 }
 
+#[test]
+fn configuration_read_ids() {
+    setup();
+
+    let mut info = SubsystemInfo::invalid();
+    info.pci_vid = 0x1b96;
+    info.pci_did = 0x2600;
+    info.pci_svid = 0x1b96;
+    info.pci_sdid = 0x0001;
+
+    let mut subsys = Subsystem::new(info);
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let _ = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x26, 0xf6, 0x4d, 0x1a
+    ];
+
+    #[rustfmt::skip]
+    const RESP: [u8; 15] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x96, 0x1b, 0x00, 0x26,
+        0x07, 0xd9, 0xd5, 0xa0
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_subsystem_ids() {
+    setup();
+
+    let mut info = SubsystemInfo::invalid();
+    info.pci_vid = 0x1b96;
+    info.pci_did = 0x2600;
+    info.pci_svid = 0x1b96;
+    info.pci_sdid = 0x0001;
+
+    let mut subsys = Subsystem::new(info);
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let _ = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0x2c, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x08, 0x15, 0x6b, 0x74
+    ];
+
+    #[rustfmt::skip]
+    const RESP: [u8; 15] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x96, 0x1b, 0x01, 0x00,
+        0x46, 0x28, 0x6b, 0xb5
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_custom_ids() {
+    setup();
+
+    let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let ctlrid = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    let ctlr = subsys.controller_mut(ctlrid);
+    ctlr.set_pcie_configuration_space(
+        PciDeviceFunctionConfigurationSpace::builder()
+            .vid(0x1b96)
+            .did(0x2600)
+            .build(),
+    );
+
+    let pcie = ctlr.pcie_configuration_space();
+    assert_eq!(pcie.vid(), 0x1b96);
+    assert_eq!(pcie.did(), 0x2600);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x26, 0xf6, 0x4d, 0x1a
+    ];
+
+    #[rustfmt::skip]
+    const RESP: [u8; 15] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x96, 0x1b, 0x00, 0x26,
+        0x07, 0xd9, 0xd5, 0xa0
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_vpd() {
+    setup();
+
+    let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let ctlrid = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    // Identifier String large resource
+    const VPD: [u8; 11] = [
+        0x82, 0x08, 0x00, b'N', b'V', b'M', b'e', b'-', b'M', b'I', 0x78,
+    ];
+
+    let ctlr = subsys.controller_mut(ctlrid);
+    ctlr.set_pcie_configuration_space(
+        PciDeviceFunctionConfigurationSpace::builder()
+            .vpd(&VPD)
+            .build(),
+    );
+
+    // The PCIe Capability links to the VPD Capability
+    #[rustfmt::skip]
+    const REQ_PCIE_CAP: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x02, 0x00, 0x00, 0x00,
+        0x48, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x6e, 0xc1, 0x52, 0xe5
+    ];
+
+    #[rustfmt::skip]
+    const RESP_PCIE_CAP: [u8; 13] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x10, 0x7c,
+        0xc1, 0xa6, 0xce, 0xf5
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP_PCIE_CAP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ_PCIE_CAP, MsgIC(true), resp, async |_| {
+            Ok(())
+        })
+        .await
+    });
+
+    // Request the dword at VPD address 4 with F clear
+    subsys
+        .controller_mut(ctlrid)
+        .pcie_configuration_space_mut()
+        .write_vpd_address(0x0004);
+
+    #[rustfmt::skip]
+    const REQ_VPD_CAP: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x08, 0x00, 0x00, 0x00,
+        0x7c, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x3f, 0x50, 0xb8, 0xa1
+    ];
+
+    // F is set on completion, with the dword in the VPD Data register
+    #[rustfmt::skip]
+    const RESP_VPD_CAP: [u8; 19] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x03, 0x00, 0x04, 0x80,
+        0x56, 0x4d, 0x65, 0x2d,
+        0x04, 0x1a, 0x55, 0xd1
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP_VPD_CAP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ_VPD_CAP, MsgIC(true), resp, async |_| {
+            Ok(())
+        })
+        .await
+    })
+}
+
+#[test]
+fn configuration_read_capability_list() {
+    setup();
+
+    let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let ctlrid = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    const VPD: [u8; 4] = [0x82, 0x01, 0x00, 0x00];
+
+    let ctlr = subsys.controller_mut(ctlrid);
+    ctlr.set_pcie_configuration_space(
+        PciDeviceFunctionConfigurationSpace::builder()
+            .msi(2)
+            .msix(0x1f, 0, 0x2000, 0, 0x3000)
+            .vpd(&VPD)
+            .build(),
+    );
+
+    // Read the capability list from the Capabilities Pointer value
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x68, 0x00, 0x00, 0x00,
+        0x40, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0xcf, 0x6a, 0x1c, 0x1d
+    ];
+
+    // Capabilities are found at (7 + offset - 0x40) in the response
+    #[rustfmt::skip]
+    let resp_fields: Vec<ExpectedField> = vec![
+        (0, &[0xa0]),
+        (3, &[0x00]), // Status
+        (7, &[0x01, 0x48]), // Power Management, next at 0x48
+        (15, &[0x10, 0x7c]), // PCI Express, next at 0x7c
+        (67, &[
+            0x05, 0x94, // MSI, next at 0x94
+            0x84, 0x01, // 64-bit, per-vector masking, 4 vectors
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ]),
+        (91, &[
+            0x11, 0xa0, // MSI-X, next at 0xa0
+            0x1f, 0x00, // 32 entries
+            0x00, 0x20, 0x00, 0x00, // Table in BAR0 at 0x2000
+            0x00, 0x30, 0x00, 0x00, // PBA in BAR0 at 0x3000
+        ]),
+        (103, &[
+            0x03, 0x00, // VPD, end of list
+            0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ]),
+    ];
+
+    let resp = RelaxedRespChannel::new(resp_fields);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_msix() {
+    setup();
+
+    let mut subsys = Subsystem::new(SubsystemInfo::invalid());
+    let ppid = subsys.add_port(PortType::Pcie(PciePort::new())).unwrap();
+    let ctlrid = subsys.add_controller(ppid).unwrap();
+    let twpid = subsys
+        .add_port(PortType::TwoWire(TwoWirePort::new()))
+        .unwrap();
+    let mut mep = ManagementEndpoint::new(twpid);
+
+    let ctlr = subsys.controller_mut(ctlrid);
+    ctlr.set_pcie_configuration_space(
+        PciDeviceFunctionConfigurationSpace::builder()
+            .msix(0x3f, 4, 0, 4, 0x800)
+            .build(),
+    );
+
+    // Without MSI, MSI-X directly follows the PCIe Capability
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x0c, 0x00, 0x00, 0x00,
+        0x7c, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0xff, 0x00, 0x7f, 0x64
+    ];
+
+    #[rustfmt::skip]
+    const RESP: [u8; 23] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x11, 0x00, 0x3f, 0x00,
+        0x04, 0x00, 0x00, 0x00,
+        0x04, 0x08, 0x00, 0x00,
+        0xb1, 0xf5, 0x6d, 0xbe
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_link() {
+    setup();
+
+    let mut t = TestDevice::new();
+    t.subsys.add_controller(t.ppid).unwrap();
+    let pprt = PciePort::builder()
+        .mls(PcieLinkSpeed::Gts8)
+        .cls(PcieLinkSpeed::Gts8)
+        .mlw(PcieLinkWidth::X4)
+        .nlw(PcieLinkWidth::X4)
+        .build();
+    t.subsys
+        .set_port_type(t.ppid, PortType::Pcie(pprt))
+        .unwrap();
+
+    // Link Capabilities, Link Control and Link Status
+    #[rustfmt::skip]
+    const REQ_LINK: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x08, 0x00, 0x00, 0x00,
+        0x54, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x7c, 0x31, 0x83, 0xee
+    ];
+
+    // 8GT/s, x4
+    #[rustfmt::skip]
+    const RESP_LINK: [u8; 19] = [
+        0xa0, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x43, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x43, 0x00,
+        0xf0, 0x37, 0xff, 0xdc
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP_LINK);
+    smol::block_on(async {
+        t.mep
+            .handle_async(
+                &mut t.subsys,
+                &REQ_LINK,
+                MsgIC(true),
+                resp,
+                async |_| Ok(()),
+            )
+            .await
+    });
+
+    // The Port Information data structure reports the same link
+    #[rustfmt::skip]
+    const REQ_PORT_INFORMATION: [u8; 19] = [
+        0x08, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00,
+        0x4e, 0x6f, 0x17, 0x3f
+    ];
+
+    #[rustfmt::skip]
+    let resp_fields: Vec<ExpectedField> = vec![
+        (0, &[0x88]),
+        (3, &[0x00]), // Status
+        (16, &[0x07, 0x03, 0x04, 0x04]), // PCIESLSV, PCIECLS, PCIEMLW, PCIENLW
+    ];
+
+    let resp = RelaxedRespChannel::new(resp_fields);
+    smol::block_on(async {
+        t.mep
+            .handle_async(
+                &mut t.subsys,
+                &REQ_PORT_INFORMATION,
+                MsgIC(true),
+                resp,
+                async |_| Ok(()),
+            )
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_bad_ctlid() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x60, 0xa3, 0x4a, 0x4e
+    ];
+
+    // MI v2.0, 4.1.2, Figure 30: PEL byte 6
+    #[rustfmt::skip]
+    const RESP: [u8; 11] = [
+        0xa0, 0x00, 0x00,
+        0x04, 0x00, 0x06, 0x00,
+        0x0b, 0x4b, 0xec, 0x43
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_bad_offset() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0x00, 0x10, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x80, 0x0c, 0x16, 0x86
+    ];
+
+    // MI v2.0, 4.1.2, Figure 30: PEL byte 12
+    #[rustfmt::skip]
+    const RESP: [u8; 11] = [
+        0xa0, 0x00, 0x00,
+        0x04, 0x00, 0x0c, 0x00,
+        0x5d, 0xb8, 0xbd, 0xf9
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_bad_length() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0x04, 0x00, 0x00, 0x00,
+        0xfe, 0x0f, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x7e, 0xf6, 0xac, 0x1f
+    ];
+
+    // MI v2.0, 4.1.2, Figure 30: PEL byte 8
+    #[rustfmt::skip]
+    const RESP: [u8; 11] = [
+        0xa0, 0x00, 0x00,
+        0x04, 0x00, 0x08, 0x00,
+        0x81, 0xd9, 0x37, 0xb7
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
+#[test]
+fn configuration_read_max_length() {
+    setup();
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    #[rustfmt::skip]
+    const REQ: [u8; 23] = [
+        0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // PCIe Request DWORD 0
+        0xff, 0xff, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+
+        // MIC
+        0x3a, 0xee, 0x33, 0x75
+    ];
+
+    // MI v2.0, 4.1.2, Figure 30: PEL byte 8
+    #[rustfmt::skip]
+    const RESP: [u8; 11] = [
+        0xa0, 0x00, 0x00,
+        0x04, 0x00, 0x08, 0x00,
+        0x81, 0xd9, 0x37, 0xb7
+    ];
+
+    let resp = ExpectedRespChannel::new(&RESP);
+    smol::block_on(async {
+        mep.handle_async(&mut subsys, &REQ, MsgIC(true), resp, async |_| Ok(()))
+            .await
+    })
+}
+
 #[test]
 fn configuration_write_invalid_short() {
     setup();
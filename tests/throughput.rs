@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-only
+/*
+ * Copyright (c) 2025 Code Construct
+ */
+mod common;
+
+use std::time::Instant;
+
+use mctp::MsgIC;
+use nvme_mi_dev::{ManagementEndpoint, Subsystem};
+
+use crate::common::{DeviceType, ExpectedField, RelaxedRespChannel, new_device};
+
+const ITERATIONS: u32 = 10_000;
+
+#[rustfmt::skip]
+const REQ_IDENTIFY_CONTROLLER: [u8; 71] = [
+    0x10, 0x00, 0x00,
+    0x06, 0x00, 0x00, 0x00,
+
+    // SQE DWORD 1
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+
+    // DOFST
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x10, 0x00, 0x00,
+
+    // Reserved
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+
+    // SQE DWORD 10
+    0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+
+    // MIC
+    0x9c, 0xd6, 0x53, 0xed
+];
+
+fn identify_controller_request(mep: &mut ManagementEndpoint, subsys: &mut Subsystem) {
+    let resp_fields: Vec<ExpectedField> = vec![(0, &[0x90])];
+    let resp = RelaxedRespChannel::new(resp_fields);
+    smol::block_on(async {
+        mep.handle_async(
+            subsys,
+            &REQ_IDENTIFY_CONTROLLER,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+}
+
+// Measures request handling dominated by the message integrity check over a
+// 4KiB response. Run with:
+//
+//   cargo test --release --test throughput -- --ignored --nocapture
+//
+// and compare against a build with `--features crc-slice16`.
+#[test]
+#[ignore = "benchmark"]
+fn identify_controller() {
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        identify_controller_request(&mut mep, &mut subsys);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "Identify Controller: {:?} per request over {ITERATIONS} requests",
+        elapsed / ITERATIONS
+    );
+}
+
+// Bounds the stack consumed servicing an Identify Controller request, with the
+// management endpoint and subsystem also placed on the thread's stack. Run
+// with:
+//
+//   cargo test --release --test throughput -- --ignored
+//
+// Encoding 4KiB responses into the endpoint scratch space reduced the
+// minimum stack for this request in release builds from roughly 103KiB to
+// 86KiB.
+#[test]
+#[ignore = "benchmark"]
+fn identify_controller_stack() {
+    const STACK_SIZE: usize = 96 * 1024;
+
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+            identify_controller_request(&mut mep, &mut subsys);
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-only
+/*
+ * Copyright (c) 2025 Code Construct
+ */
+#![cfg(feature = "trace-responses")]
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use mctp::MsgIC;
+mod common;
+
+use common::DeviceType;
+use common::ExpectedRespChannel;
+use common::new_device;
+
+struct CaptureLogger(Mutex<Vec<String>>);
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() == Level::Trace
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) && record.target().starts_with("nvme_mi_dev") {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+#[rustfmt::skip]
+const REQ_HEALTH_STATUS_POLL: [u8; 19] = [
+    0x08, 0x00, 0x00,
+    0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x80,
+    0xaa, 0xef, 0x81, 0xb4
+];
+
+#[rustfmt::skip]
+const RESP_HEALTH_STATUS_POLL: [u8; 19] = [
+    0x88, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x38, 0x3d, 0x14, 0x26,
+    0x00, 0x00, 0x00, 0x00,
+    0x11, 0x7c, 0xb0, 0x3d
+];
+
+#[test]
+fn response_hex_dump() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let (mut mep, mut subsys) = new_device(DeviceType::P1p1tC1iN0a0a);
+
+    let resp = ExpectedRespChannel::new(&RESP_HEALTH_STATUS_POLL);
+    smol::block_on(async {
+        mep.handle_async(
+            &mut subsys,
+            &REQ_HEALTH_STATUS_POLL,
+            MsgIC(true),
+            resp,
+            async |_| Ok(()),
+        )
+        .await
+    });
+
+    assert_eq!(
+        *LOGGER.0.lock().unwrap(),
+        [
+            "0000: [88, 00, 00, 00, 00, 00, 00, 38, 3d, 14, 26, 00, 00, 00, 00, 11]",
+            "0010: [7c, b0, 3d]",
+        ]
+    );
+}